@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use tictactoe::{game::Game, game_tree::GameTree};
+
+// building the full 3x3 tree, the baseline for caching and parallel construction
+fn build(c: &mut Criterion) {
+    c.bench_function("GameTree::from", |b| {
+        b.iter(|| GameTree::from(black_box(Game::new())))
+    });
+    c.bench_function("GameTree::from_cached", |b| {
+        b.iter(|| GameTree::from_cached(black_box(Game::new())))
+    });
+}
+
+// the first four plies on a 4x4 board, run with and without --features rayon to see what
+// building subtrees on separate threads gains
+fn build_4x4(c: &mut Criterion) {
+    c.bench_function("GameTree::from_depth 4x4", |b| {
+        b.iter(|| GameTree::from_depth(black_box(Game::with_size(4)), 4))
+    });
+}
+
+// a tree big enough for threads to meet on the board interner, run with --features rayon
+// and compared against a baseline saved before boards were interned to check that sharing
+// them doesn't hold back the parallel build
+fn build_4x4_interned(c: &mut Criterion) {
+    c.bench_function("GameTree::from_depth 4x4 depth 5", |b| {
+        b.iter(|| GameTree::from_depth(black_box(Game::with_size(4)), 5))
+    });
+}
+
+// probabilities on an already built tree
+fn evaluate(c: &mut Criterion) {
+    let game_tree = GameTree::from(Game::new());
+    c.bench_function("GameTree::x_wins", |b| {
+        b.iter(|| black_box(&game_tree).x_wins())
+    });
+}
+
+criterion_group!(benches, build, build_4x4, build_4x4_interned, evaluate);
+criterion_main!(benches);
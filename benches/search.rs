@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use tictactoe::game::Game;
+use tictactoe::search::{alphabeta, nodes_visited, ordered_moves, MoveGen};
+
+// plain minimax against alpha-beta trying moves unordered and ordered, each labeled with
+// the number of positions it visits from the opening
+fn pruning(c: &mut Criterion) {
+    let searches: [(&str, MoveGen, bool); 3] = [
+        ("minimax", Game::valid_moves, false),
+        ("alphabeta unordered", Game::valid_moves, true),
+        ("alphabeta ordered", ordered_moves, true),
+    ];
+    let mut group = c.benchmark_group("search");
+    for (name, move_gen, prune) in searches {
+        let nodes = nodes_visited(&Game::new(), move_gen, prune);
+        let id = BenchmarkId::new(name, format!("{} positions", nodes));
+        group.bench_function(id, |b| {
+            b.iter(|| nodes_visited(black_box(&Game::new()), move_gen, prune))
+        });
+    }
+    group.finish();
+}
+
+// the library search, which runs on a bitboard for 3x3, against the Game based searches
+// above
+fn bitboard(c: &mut Criterion) {
+    c.bench_function("alphabeta bitboard", |b| {
+        b.iter(|| alphabeta(black_box(&Game::new()), -1, 1))
+    });
+}
+
+criterion_group!(benches, pruning, bitboard);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+use crate::game::{Game, ParseError, Piece, Ruleset, Winner};
+
+// the eight lines of a 3x3 board, with bit row * 3 + col set for each of their cells, in
+// the order Game checks lines in so that both find the same first completed line
+const WIN_MASKS: [u16; 8] = [
+    0b000_000_111,
+    0b001_001_001,
+    0b100_010_001,
+    0b010_010_010,
+    0b100_100_100,
+    0b001_010_100,
+    0b000_111_000,
+    0b111_000_000,
+];
+
+const FULL: u16 = 0b111_111_111;
+
+// cells to try first in a search: the center, then the corners, then the edges
+pub const MOVE_ORDER: [usize; 9] = [4, 0, 2, 6, 8, 1, 3, 5, 7];
+
+// a 3x3 board as one bit per cell for each piece, cell row * 3 + col, cheap to copy and to
+// check for a win
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BitBoard {
+    x: u16,
+    o: u16,
+}
+
+impl BitBoard {
+    // None unless game is 3x3 with three in a row winning under the normal rules
+    pub fn from_game(game: &Game) -> Option<Self> {
+        let board = game.board();
+        if board.num_rows() != 3
+            || board.num_columns() != 3
+            || game.win_length() != 3
+            || game.ruleset() != Ruleset::Normal
+        {
+            return None;
+        }
+        Some(
+            game.occupied_cells()
+                .fold(BitBoard::default(), |bits, ((row, col), piece)| {
+                    bits.with_piece(row * 3 + col, piece)
+                }),
+        )
+    }
+
+    fn pieces(&self, piece: Piece) -> u16 {
+        match piece {
+            Piece::X => self.x,
+            Piece::O => self.o,
+        }
+    }
+
+    pub fn piece_at(&self, cell: usize) -> Option<Piece> {
+        if self.x & 1 << cell != 0 {
+            Some(Piece::X)
+        } else if self.o & 1 << cell != 0 {
+            Some(Piece::O)
+        } else {
+            None
+        }
+    }
+
+    // the board with piece added at cell, which must be empty
+    pub fn with_piece(self, cell: usize, piece: Piece) -> Self {
+        match piece {
+            Piece::X => Self {
+                x: self.x | 1 << cell,
+                ..self
+            },
+            Piece::O => Self {
+                o: self.o | 1 << cell,
+                ..self
+            },
+        }
+    }
+
+    // the owner of the first completed line, so a set up board with lines for both pieces
+    // has the same winner as the Game it came from
+    pub fn winner(&self) -> Option<Winner> {
+        for mask in WIN_MASKS {
+            for piece in [Piece::X, Piece::O] {
+                if self.pieces(piece) & mask == mask {
+                    return Some(piece.into());
+                }
+            }
+        }
+        (self.x | self.o == FULL).then_some(Winner::Tie)
+    }
+}
+
+impl TryFrom<BitBoard> for Game {
+    type Error = ParseError;
+
+    fn try_from(bits: BitBoard) -> Result<Self, Self::Error> {
+        let rows: Vec<Vec<_>> = (0..3)
+            .map(|row| (0..3).map(|col| bits.piece_at(row * 3 + col)).collect())
+            .collect();
+        Game::try_from(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::terminal_games;
+
+    #[test]
+    fn bitboard_winner_agrees_on_every_finished_position() {
+        // parsing the board again finds its winner by checking every line
+        for game in terminal_games(&Game::new()) {
+            let bits = BitBoard::from_game(&game).unwrap();
+            let rescanned = Game::from_compact(&game.to_compact()).unwrap();
+            assert_eq!(bits.winner(), rescanned.winner, "{}", game);
+            assert_eq!(Game::try_from(bits).unwrap().board(), game.board());
+        }
+        assert_eq!(BitBoard::from_game(&Game::new()).unwrap().winner(), None);
+    }
+
+    #[test]
+    fn bitboard_winner_agrees_on_a_double_win() {
+        // o's top row comes before x's middle row, so o has won and x to move has lost
+        let game = Game::from_compact("OOOXXX...").unwrap();
+        assert_eq!(game.winner, Some(Winner::O));
+        assert_eq!(BitBoard::from_game(&game).unwrap().winner(), game.winner);
+        assert_eq!(crate::search::alphabeta(&game, -1, 1), -1);
+    }
+
+    #[test]
+    fn bitboard_is_only_for_plain_3x3() {
+        assert!(BitBoard::from_game(&Game::with_size(4)).is_none());
+        assert!(BitBoard::from_game(&Game::with_rules(3, 2)).is_none());
+        assert!(BitBoard::from_game(&Game::new().with_ruleset(Ruleset::Misere).unwrap()).is_none());
+    }
+}
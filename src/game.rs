@@ -1,16 +1,23 @@
 use array2d::Array2D;
 use itertools::Itertools;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Index;
+use std::str::FromStr;
+use std::sync::Arc;
 
 const BOARD_SIZE: usize = 3;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     X,
     O,
 }
 impl Piece {
-    fn other(&self) -> Piece {
+    pub fn other(&self) -> Piece {
         match self {
             Piece::X => Piece::O,
             Piece::O => Piece::X,
@@ -18,7 +25,27 @@ impl Piece {
     }
 }
 
+// accepts either case, and 0 for O
+impl TryFrom<char> for Piece {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'X' | 'x' => Ok(Piece::X),
+            'O' | 'o' | '0' => Ok(Piece::O),
+            found => Err(ParseError::InvalidPiece { found }),
+        }
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", to_char(&Some(*self)))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Winner {
     X,
     O,
@@ -34,17 +61,219 @@ impl From<Piece> for Winner {
     }
 }
 
+impl fmt::Display for Winner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Winner::X => write!(f, "X"),
+            Winner::O => write!(f, "O"),
+            Winner::Tie => write!(f, "Tie"),
+        }
+    }
+}
+
+// a result seen from one player's side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+pub fn outcome_for(winner: Winner, piece: Piece) -> Outcome {
+    match winner {
+        Winner::Tie => Outcome::Draw,
+        winner if winner == Winner::from(piece) => Outcome::Win,
+        _ => Outcome::Loss,
+    }
+}
+
+// who can still complete a line of win_length cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineStatus {
+    // no pieces yet
+    Open,
+    // only this piece, so only it can complete the line
+    OwnedBy(Piece),
+    // both pieces, so nobody can complete the line
+    Dead,
+}
+
+// what a legal move did to the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveOutcome {
+    Continued,
+    Won(Piece),
+    Tied,
+}
+
 pub type Board = Array2D<Option<Piece>>;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ruleset {
+    // completing a line wins
+    #[default]
+    Normal,
+    // completing a line loses
+    Misere,
+    // completing a line wins, but a piece drops to the lowest empty cell of the column it
+    // is played in
+    Gravity,
+}
+
+// deserializing goes through SavedGame, so a file can't make an inconsistent game
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SavedGame"))]
 pub struct Game {
-    board: Board,
+    // shared between games until one of them changes it, see intern_board
+    #[cfg_attr(feature = "serde", serde(serialize_with = "board_serde::serialize"))]
+    board: Arc<Board>,
     current_piece: Piece,
     pub winner: Option<Winner>,
+    win_length: usize,
+    ruleset: Ruleset,
+    history: Vec<(usize, usize)>,
+    // who moved first, what reset goes back to
+    starting_piece: Piece,
+}
+
+// Array2D has no serde support, so the board is stored as a list of rows
+#[cfg(feature = "serde")]
+mod board_serde {
+    use super::{Board, Piece};
+    use array2d::Array2D;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(board: &Arc<Board>, serializer: S) -> Result<S::Ok, S::Error> {
+        board.as_rows().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<Board>, D::Error> {
+        let rows = Vec::<Vec<Option<Piece>>>::deserialize(deserializer)?;
+        if rows.is_empty() || rows[0].is_empty() {
+            return Err(D::Error::custom("board must have at least one cell"));
+        }
+        Array2D::from_rows(&rows)
+            .map(Arc::new)
+            .map_err(|e| D::Error::custom(format!("{:?}", e)))
+    }
+}
+
+// a game as read from a file, checked before it becomes a Game, the winner is always
+// worked out again from the board rather than trusted
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedGame {
+    #[serde(deserialize_with = "board_serde::deserialize")]
+    board: Arc<Board>,
+    current_piece: Piece,
+    win_length: usize,
+    ruleset: Ruleset,
+    history: Vec<(usize, usize)>,
+    // missing from games saved before it was recorded
+    #[serde(default)]
+    starting_piece: Option<Piece>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SavedGame> for Game {
+    type Error = String;
+
+    fn try_from(saved: SavedGame) -> Result<Self, Self::Error> {
+        let board = saved.board;
+        let (rows, cols) = (board.num_rows(), board.num_columns());
+        if !(1..=rows.min(cols)).contains(&saved.win_length) {
+            return Err(format!(
+                "win length {} doesn't fit a {}x{} board",
+                saved.win_length, rows, cols
+            ));
+        }
+
+        // undoing the history from the end must take back alternating pieces, each from a
+        // different cell
+        let mut piece = saved.current_piece;
+        let mut undone = HashSet::new();
+        for &(row, col) in saved.history.iter().rev() {
+            piece = piece.other();
+            if board.get(row, col) != Some(&Some(piece)) || !undone.insert((row, col)) {
+                return Err(format!(
+                    "history move ({},{}) doesn't match the board",
+                    row, col
+                ));
+            }
+        }
+
+        let starting_piece = saved.starting_piece.unwrap_or_else(|| {
+            let count = |piece| {
+                board
+                    .elements_row_major_iter()
+                    .filter(|&&p| p == Some(piece))
+                    .count()
+            };
+            if count(Piece::O) > count(Piece::X) {
+                Piece::O
+            } else {
+                Piece::X
+            }
+        });
+        let winner = to_winner(&board, saved.win_length, saved.ruleset);
+        Ok(Game {
+            board,
+            current_piece: saved.current_piece,
+            winner,
+            win_length: saved.win_length,
+            ruleset: saved.ruleset,
+            history: saved.history,
+            starting_piece,
+        })
+    }
+}
+
+// games are equal when they reach the same position, whatever the move order
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.current_piece == other.current_piece
+            && self.winner == other.winner
+            && self.win_length == other.win_length
+            && self.ruleset == other.ruleset
+    }
+}
+
+impl Eq for Game {}
+
+impl Hash for Game {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.hash(state);
+        self.current_piece.hash(state);
+        self.winner.hash(state);
+        self.win_length.hash(state);
+        self.ruleset.hash(state);
+    }
+}
+
+// a game compared and hashed up to rotation and reflection, so symmetric positions
+// collapse to one entry in a HashSet or HashMap
+#[derive(Debug, Clone)]
+pub struct CanonicalGame(pub Game);
+
+impl PartialEq for CanonicalGame {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.is_symmetric_to(&other.0)
+    }
+}
+
+impl Eq for CanonicalGame {}
+
+impl Hash for CanonicalGame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.canonical().hash(state);
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum MoveError {
     GameAlreadyOver,
     InvalidPosition {
@@ -56,49 +285,208 @@ pub enum MoveError {
         row: usize,
         col: usize,
     },
+    NothingToUndo,
+    ColumnFull {
+        col: usize,
+    },
+    GameStarted,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::GameAlreadyOver => write!(f, "game is already over"),
+            MoveError::InvalidPosition { row, col } => {
+                write!(f, "position ({},{}) is outside the board", row, col)
+            }
+            MoveError::TileNotEmpty {
+                other_piece,
+                row,
+                col,
+            } => write!(
+                f,
+                "tile ({},{}) already occupied by {}",
+                row, col, other_piece
+            ),
+            MoveError::NothingToUndo => write!(f, "no moves to undo"),
+            MoveError::ColumnFull { col } => write!(f, "column {} is full", col),
+            MoveError::GameStarted => write!(f, "game has already started"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    EmptyBoard,
+    InvalidCell {
+        row: usize,
+        col: usize,
+        cell: String,
+    },
+    InconsistentDimensions {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidPieceCount {
+        x_count: usize,
+        o_count: usize,
+    },
+    InvalidLength {
+        length: usize,
+    },
+    InvalidNotation {
+        input: String,
+    },
+    CellOutOfRange {
+        row: usize,
+        col: usize,
+    },
+    InvalidPiece {
+        found: char,
+    },
 }
 
-fn to_winner(board: &Board) -> Option<Winner> {
-    // Check rows
-    for (i, mut row) in board.rows_iter().enumerate() {
-        let first = board[(i, 0)];
-        if first.is_some() && row.all(|&p| p == first) {
-            return Some(first.unwrap().into());
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::EmptyBoard => write!(f, "board has no rows"),
+            ParseError::InvalidCell { row, col, cell } => {
+                write!(f, "invalid cell {:?} at ({},{})", cell, row, col)
+            }
+            ParseError::InconsistentDimensions {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} has {} cells, expected {}", row, found, expected),
+            ParseError::InvalidPieceCount { x_count, o_count } => write!(
+                f,
+                "{} X and {} O pieces can't be reached by alternating moves",
+                x_count, o_count
+            ),
+            ParseError::InvalidLength { length } => {
+                write!(f, "{} cells can't form a square board", length)
+            }
+            ParseError::InvalidNotation { input } => {
+                write!(
+                    f,
+                    "{:?} isn't a column letter and row number like b2",
+                    input
+                )
+            }
+            ParseError::CellOutOfRange { row, col } => {
+                write!(f, "{} is off the board", move_to_notation((*row, *col)))
+            }
+            ParseError::InvalidPiece { found } => write!(f, "{:?} isn't X or O", found),
         }
     }
+}
+
+impl std::error::Error for ParseError {}
+
+// true if every cell of the line holds the same piece
+fn is_complete(board: &Board, line: &[(usize, usize)]) -> bool {
+    let first = board[line[0]];
+    first.is_some() && line.iter().all(|&pos| board[pos] == first)
+}
+
+// coordinates of win_length cells in a row, column or diagonal all holding the same piece,
+// the first of them in the order of completed_lines when there is more than one
+fn completed_line(board: &Board, win_length: usize) -> Option<Vec<(usize, usize)>> {
+    completed_lines(board, win_length).into_iter().next()
+}
+
+// every completed line, ordered by the row-major position of the line's first cell and
+// then by direction as listed in DIRECTIONS
+fn completed_lines(board: &Board, win_length: usize) -> Vec<Vec<(usize, usize)>> {
+    assert!((1..=board.num_rows().min(board.num_columns())).contains(&win_length));
+    lines(board, win_length)
+        .into_iter()
+        .filter(|line| is_complete(board, line))
+        .collect()
+}
+
+// the winner when piece completes a line
+fn line_winner(piece: Piece, ruleset: Ruleset) -> Winner {
+    match ruleset {
+        Ruleset::Normal | Ruleset::Gravity => piece.into(),
+        Ruleset::Misere => piece.other().into(),
+    }
+}
+
+// what heuristic_score counts a completed line as, against 1 for a line that is only
+// partly filled
+pub const WON_LINE_SCORE: i32 = 100;
+
+// directions a line can run in: across, down, down-right and down-left
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// every row, column and diagonal window of win_length cells on a square board of
+// board_size, the lines that can win the game
+pub fn winning_lines(board_size: usize, win_length: usize) -> Vec<Vec<(usize, usize)>> {
+    lines_in(board_size, board_size, win_length)
+}
+
+fn lines(board: &Board, win_length: usize) -> Vec<Vec<(usize, usize)>> {
+    lines_in(board.num_rows(), board.num_columns(), win_length)
+}
+
+// every row, column and diagonal window of win_length cells on a rows x cols board
+pub fn lines_in(rows: usize, cols: usize, win_length: usize) -> Vec<Vec<(usize, usize)>> {
+    // a single cell is a line in every direction, so count it once
+    if win_length == 1 {
+        return (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| vec![(row, col)]))
+            .collect();
+    }
 
-    // Check columns
-    for (i, mut col) in board.columns_iter().enumerate() {
-        let first = board[(0, i)];
-        if first.is_some() && col.all(|&p| p == first) {
-            return Some(first.unwrap().into());
+    let rows = rows as isize;
+    let cols = cols as isize;
+    let last = win_length as isize - 1;
+    let mut lines = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            for (d_row, d_col) in DIRECTIONS {
+                // a straight line fits on the board if both of its ends do
+                let (end_row, end_col) = (row + d_row * last, col + d_col * last);
+                if (0..rows).contains(&end_row) && (0..cols).contains(&end_col) {
+                    lines.push(
+                        (0..=last)
+                            .map(|i| ((row + d_row * i) as usize, (col + d_col * i) as usize))
+                            .collect(),
+                    );
+                }
+            }
         }
     }
+    lines
+}
 
-    // check first diag
-    assert_eq!(board.num_rows(), board.num_columns());
-    let top_left = board[(0, 0)];
-    if top_left.is_some()
-        && (1..board.num_rows())
-            .map(|i| board[(i, i)])
-            .all(|p| p == top_left)
-    {
-        return Some(top_left.unwrap().into());
-    }
-
-    // check second diag
-    let max_index = board.num_rows() - 1;
-    let top_right = board[(0, max_index)];
-    if top_right.is_some()
-        && (1..board.num_rows())
-            .map(|i| {
-                let row = i;
-                let col = max_index - i;
-                board[(row, col)]
-            })
-            .all(|p| p == top_right)
-    {
-        return Some(top_right.unwrap().into());
+// the empty cell of each line that is one piece short of a win for piece
+fn threat_cells(board: &Board, lines: &[Vec<(usize, usize)>], piece: Piece) -> Vec<(usize, usize)> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let mut empty = line.iter().filter(|&&pos| board[pos].is_none());
+            let cell = *empty.next()?;
+            let rest_owned = empty.next().is_none()
+                && line
+                    .iter()
+                    .filter(|&&pos| board[pos] == Some(piece))
+                    .count()
+                    == line.len() - 1;
+            rest_owned.then_some(cell)
+        })
+        .collect()
+}
+
+// a board with lines completed by both pieces, only possible in a set up position, is
+// won by the owner of the first completed line in the order of completed_lines
+fn to_winner(board: &Board, win_length: usize, ruleset: Ruleset) -> Option<Winner> {
+    if let Some(line) = completed_line(board, win_length) {
+        return board[line[0]].map(|piece| line_winner(piece, ruleset));
     }
 
     // test for tie
@@ -109,91 +497,2126 @@ fn to_winner(board: &Board) -> Option<Winner> {
     None
 }
 
+// number of cells holding piece that follow pos in the direction (d_row, d_col)
+fn run_length(board: &Board, (row, col): (usize, usize), (d_row, d_col): (isize, isize)) -> usize {
+    let piece = board[(row, col)];
+    (1..)
+        .map(|i| (row as isize + d_row * i, col as isize + d_col * i))
+        .take_while(|&(r, c)| r >= 0 && c >= 0 && board.get(r as usize, c as usize) == Some(&piece))
+        .count()
+}
+
+// true if the piece at pos is part of win_length or more cells in a row holding it
+fn completes_line_at(board: &Board, win_length: usize, pos: (usize, usize)) -> bool {
+    DIRECTIONS.iter().any(|&(d_row, d_col)| {
+        1 + run_length(board, pos, (d_row, d_col)) + run_length(board, pos, (-d_row, -d_col))
+            >= win_length
+    })
+}
+
+// same result as to_winner for a board that had no winner before the piece at (row, col)
+// was placed, only the lines through that cell can have been completed by it
+fn check_win_at(
+    board: &Board,
+    win_length: usize,
+    ruleset: Ruleset,
+    row: usize,
+    col: usize,
+) -> Option<Winner> {
+    let piece = board[(row, col)]?;
+    if completes_line_at(board, win_length, (row, col)) {
+        return Some(line_winner(piece, ruleset));
+    }
+
+    if board.elements_row_major_iter().all(|p| p.is_some()) {
+        return Some(Winner::Tie);
+    }
+
+    None
+}
+
+// rotate a board a quarter turn clockwise
+pub fn rotate90(board: &Board) -> Board {
+    let rows = board.num_rows();
+    let cols = board.num_columns();
+    let mut rotated = Array2D::filled_with(None, cols, rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            rotated[(col, rows - 1 - row)] = board[(row, col)];
+        }
+    }
+    rotated
+}
+
+// mirror a board left to right
+pub fn reflect(board: &Board) -> Board {
+    let cols = board.num_columns();
+    let mut reflected = board.clone();
+    for row in 0..board.num_rows() {
+        for col in 0..cols {
+            reflected[(row, cols - 1 - col)] = board[(row, col)];
+        }
+    }
+    reflected
+}
+
+// one of the 8 rotations and reflections of a board: turns quarter turns clockwise, then a
+// left to right mirror if mirror is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Symmetry {
+    turns: u8,
+    mirror: bool,
+}
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry {
+        turns: 0,
+        mirror: false,
+    };
+
+    // all 8, starting with the identity
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..4).flat_map(|turns| [false, true].map(move |mirror| Symmetry { turns, mirror }))
+    }
+
+    pub fn board(self, board: &Board) -> Board {
+        let mut board = (0..self.turns).fold(board.clone(), |board, _| rotate90(&board));
+        if self.mirror {
+            board = reflect(&board);
+        }
+        board
+    }
+
+    // where the piece at cell of a rows x cols board ends up
+    pub fn cell(self, (row, col): (usize, usize), rows: usize, cols: usize) -> (usize, usize) {
+        let (mut row, mut col, mut rows, mut cols) = (row, col, rows, cols);
+        for _ in 0..self.turns {
+            (row, col, rows, cols) = (col, rows - 1 - row, cols, rows);
+        }
+        if self.mirror {
+            col = cols - 1 - col;
+        }
+        (row, col)
+    }
+
+    // this symmetry applied after other, using that a mirror followed by a turn is the
+    // same as a turn the other way followed by a mirror
+    pub fn after(self, other: Symmetry) -> Symmetry {
+        if other.mirror {
+            Symmetry {
+                turns: (other.turns + 4 - self.turns) % 4,
+                mirror: !self.mirror,
+            }
+        } else {
+            Symmetry {
+                turns: (self.turns + other.turns) % 4,
+                mirror: self.mirror,
+            }
+        }
+    }
+}
+
+// the rotations and reflections of a board with the same shape as it, all 8 for a square
+// board and 4 otherwise
+fn symmetries(board: &Board) -> Vec<(Symmetry, Board)> {
+    Symmetry::all()
+        .map(|symmetry| (symmetry, symmetry.board(board)))
+        .filter(|(_, b)| b.num_rows() == board.num_rows())
+        .collect()
+}
+
 impl Default for Game {
     fn default() -> Self {
         Game::new()
     }
 }
 
+// game[(row, col)] reads a cell, panicking if it is off the board like indexing the board
+impl Index<(usize, usize)> for Game {
+    type Output = Option<Piece>;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        &self.board[pos]
+    }
+}
+
 impl Game {
     pub fn new() -> Self {
+        Self::with_size(BOARD_SIZE)
+    }
+
+    // build an empty size x size board won by filling a whole row, column or diagonal
+    pub fn with_size(size: usize) -> Self {
+        Self::with_rules(size, size)
+    }
+
+    // build an empty size x size board won by placing win_length pieces in a line
+    pub fn with_rules(size: usize, win_length: usize) -> Self {
+        Self::with_dimensions(size, size, win_length)
+    }
+
+    // build an empty rows x cols board won by placing win_length pieces in a line
+    pub fn with_dimensions(rows: usize, cols: usize, win_length: usize) -> Self {
+        assert!(rows > 0 && cols > 0, "board must have at least one cell");
+        assert!(
+            (1..=rows.min(cols)).contains(&win_length),
+            "win length must be between 1 and the shorter side of the board"
+        );
         Self {
-            board: Array2D::filled_with(None, BOARD_SIZE, BOARD_SIZE),
+            board: Arc::new(Array2D::filled_with(None, rows, cols)),
             current_piece: Piece::X,
             winner: None,
+            win_length,
+            ruleset: Ruleset::Normal,
+            history: Vec::new(),
+            starting_piece: Piece::X,
         }
     }
 
-    pub fn is_finished(&self) -> bool {
-        self.winner.is_some()
+    // switch the rules of a board nothing has been placed on yet, meant to be chained onto
+    // one of the constructors
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Result<Self, MoveError> {
+        if self.board.elements_row_major_iter().any(Option::is_some) {
+            return Err(MoveError::GameStarted);
+        }
+        self.ruleset = ruleset;
+        Ok(self)
     }
 
-    pub fn make_move(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
-        if self.is_finished() {
-            return Err(MoveError::GameAlreadyOver);
+    // a new game where piece makes the first move
+    pub fn with_starting_piece(piece: Piece) -> Self {
+        Self {
+            current_piece: piece,
+            starting_piece: piece,
+            ..Game::new()
         }
+    }
 
-        if row >= self.board.num_rows() || col >= self.board.num_columns() {
-            return Err(MoveError::InvalidPosition { row, col });
+    // replay moves on a new game, stopping at the first illegal one
+    pub fn from_moves(moves: &[(usize, usize)]) -> Result<Self, MoveError> {
+        let mut game = Game::new();
+        for &(row, col) in moves {
+            game.make_move(row, col)?;
+        }
+        Ok(game)
+    }
+
+    // a new game with pieces placed on it directly, in any number and order, and to_move
+    // to play next
+    pub fn from_setup(
+        cells: &[((usize, usize), Piece)],
+        to_move: Piece,
+    ) -> Result<Self, MoveError> {
+        let mut game = Game::with_starting_piece(to_move);
+        for &((row, col), piece) in cells {
+            match game.board.get(row, col) {
+                None => return Err(MoveError::InvalidPosition { row, col }),
+                Some(&Some(other_piece)) => {
+                    return Err(MoveError::TileNotEmpty {
+                        other_piece,
+                        row,
+                        col,
+                    })
+                }
+                Some(None) => Arc::make_mut(&mut game.board)[(row, col)] = Some(piece),
+            }
         }
+        game.update_winner();
+        Ok(game)
+    }
 
-        if let Some(piece) = self.board[(row, col)] {
-            return Err(MoveError::TileNotEmpty {
-                other_piece: piece,
-                row,
-                col,
-            });
+    // one character per cell in row-major order, '.' for empty cells, with the rows of a
+    // board that isn't square separated by '/' so its shape can be read back
+    pub fn to_compact(&self) -> String {
+        let separator = if self.board.num_rows() == self.board.num_columns() {
+            ""
+        } else {
+            "/"
+        };
+        self.board
+            .rows_iter()
+            .map(|row| {
+                row.map(|piece| match piece {
+                    None => '.',
+                    Some(_) => to_char(piece),
+                })
+                .collect::<String>()
+            })
+            .join(separator)
+    }
+
+    // parse the output of to_compact, a string without '/' must make a square board
+    pub fn from_compact(s: &str) -> Result<Self, ParseError> {
+        let rows: Vec<Vec<char>> = if s.contains('/') {
+            s.split('/').map(|row| row.chars().collect()).collect()
+        } else {
+            let cells: Vec<char> = s.chars().collect();
+            let size = (0..=cells.len()).find(|n| n * n >= cells.len()).unwrap();
+            if size * size != cells.len() {
+                return Err(ParseError::InvalidLength {
+                    length: cells.len(),
+                });
+            }
+            cells.chunks(size.max(1)).map(<[char]>::to_vec).collect()
+        };
+        if rows.is_empty() || rows[0].is_empty() {
+            return Err(ParseError::EmptyBoard);
         }
 
-        // modify the current state
-        self.board[(row, col)] = Some(self.current_piece);
-        self.current_piece = self.current_piece.other();
-        self.winner = to_winner(&self.board);
-        Ok(())
+        let cols = rows[0].len();
+        let mut board = Array2D::filled_with(None, rows.len(), cols);
+        for (row, cells) in rows.iter().enumerate() {
+            if cells.len() != cols {
+                return Err(ParseError::InconsistentDimensions {
+                    row,
+                    expected: cols,
+                    found: cells.len(),
+                });
+            }
+            for (col, &cell) in cells.iter().enumerate() {
+                board[(row, col)] = parse_cell(cell).map_err(|_| ParseError::InvalidCell {
+                    row,
+                    col,
+                    cell: cell.to_string(),
+                })?;
+            }
+        }
+        Game::from_board(board)
     }
 
-    fn valid_moves(&self) -> Vec<(usize, usize)> {
-        let mut moves = Vec::new();
+    // build a game from a position, inferring whose turn it is from the piece counts, with
+    // o ahead by one meaning o moved first
+    fn from_board(board: Board) -> Result<Self, ParseError> {
+        let count = |piece| {
+            board
+                .elements_row_major_iter()
+                .filter(|&&p| p == Some(piece))
+                .count()
+        };
+        let x_count = count(Piece::X);
+        let o_count = count(Piece::O);
+        let (current_piece, starting_piece) = if x_count == o_count {
+            (Piece::X, Piece::X)
+        } else if x_count == o_count + 1 {
+            (Piece::O, Piece::X)
+        } else if o_count == x_count + 1 {
+            (Piece::X, Piece::O)
+        } else {
+            return Err(ParseError::InvalidPieceCount { x_count, o_count });
+        };
 
-        for row in 0..self.board.num_rows() {
-            for col in 0..self.board.num_columns() {
-                if self.board[(row, col)].is_none() {
-                    moves.push((row, col));
-                }
+        let win_length = board.num_rows().min(board.num_columns());
+        let ruleset = Ruleset::Normal;
+        let winner = to_winner(&board, win_length, ruleset);
+        Ok(Self {
+            board: Arc::new(board),
+            current_piece,
+            winner,
+            win_length,
+            ruleset,
+            history: Vec::new(),
+            starting_piece,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // swap the board for an identical one already in boards, or add this one, so games
+    // with the same position share a single copy of it
+    pub(crate) fn intern_board(&mut self, boards: &mut HashSet<Arc<Board>>) {
+        match boards.get(&self.board) {
+            Some(board) => self.board = board.clone(),
+            None => {
+                boards.insert(self.board.clone());
             }
         }
-        moves
     }
-}
 
-fn to_char(maybe_piece: &Option<Piece>) -> char {
-    match maybe_piece {
-        None => ' ',
-        Some(Piece::X) => 'X',
-        Some(Piece::O) => 'O',
+    pub fn current_piece(&self) -> Piece {
+        self.current_piece
     }
-}
 
-impl fmt::Display for Game {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let display_board = self
-            .board
-            .rows_iter()
-            .map(|row| row.map(to_char).join("|"))
-            .join("\n-----\n");
+    // number of pieces in a line needed to win
+    pub fn win_length(&self) -> usize {
+        self.win_length
+    }
 
-        write!(f, "{}\nWinner: {:?}", display_board, self.winner)
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
     }
-}
 
-pub fn next_games(game: &Game) -> Vec<Game> {
-    game.valid_moves()
-        .iter()
-        .flat_map(|(row, col)| {
-            let mut game = game.clone();
-            let res = game.make_move(*row, *col);
-            assert!(res.is_ok(), "{:?}", res.err().unwrap());
-            Ok::<Game, MoveError>(game)
-        })
-        .collect()
+    // contents of a cell, None if it is empty or off the board
+    pub fn piece_at(&self, row: usize, col: usize) -> Option<Piece> {
+        self.board.get(row, col).copied().flatten()
+    }
+
+    // the up to 8 cells touching (row, col) with their contents, in row-major order
+    pub fn neighbors(&self, row: usize, col: usize) -> Vec<((usize, usize), Option<Piece>)> {
+        (-1..=1)
+            .flat_map(|d_row| (-1..=1).map(move |d_col| (d_row, d_col)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(|(d_row, d_col)| {
+                let row = row.checked_add_signed(d_row)?;
+                let col = col.checked_add_signed(d_col)?;
+                self.board.get(row, col).map(|&piece| ((row, col), piece))
+            })
+            .collect()
+    }
+
+    // the rotations and reflections of the board that keep its shape, under gravity
+    // only the left to right mirror since the bottom row must stay at the bottom
+    fn symmetries(&self) -> Vec<(Symmetry, Board)> {
+        let mut symmetries = symmetries(&self.board);
+        if self.ruleset == Ruleset::Gravity {
+            symmetries.retain(|(symmetry, _)| symmetry.turns == 0);
+        }
+        symmetries
+    }
+
+    // the symmetric position with the smallest board, the result has no move history
+    pub fn canonical(&self) -> Game {
+        let (_, board) = self
+            .symmetries()
+            .into_iter()
+            .min_by(|(_, a), (_, b)| a.elements_row_major_iter().cmp(b.elements_row_major_iter()))
+            .unwrap();
+        self.with_board(board)
+    }
+
+    // a symmetry taking this position to other, None if other isn't one of its rotations
+    // or reflections, prefers the identity when the boards are the same
+    pub fn symmetry_to(&self, other: &Game) -> Option<Symmetry> {
+        if self.current_piece != other.current_piece
+            || self.win_length != other.win_length
+            || self.ruleset != other.ruleset
+        {
+            return None;
+        }
+        self.symmetries()
+            .into_iter()
+            .find(|(_, board)| board == other.board())
+            .map(|(symmetry, _)| symmetry)
+    }
+
+    // the position with symmetry applied to its board and to the move history
+    pub fn transformed(&self, symmetry: Symmetry) -> Game {
+        let (rows, cols) = (self.board.num_rows(), self.board.num_columns());
+        let mut game = self.with_board(symmetry.board(&self.board));
+        game.history = self
+            .history
+            .iter()
+            .map(|&cell| symmetry.cell(cell, rows, cols))
+            .collect();
+        game
+    }
+
+    // same rules and player to move on another board, with no move history
+    fn with_board(&self, board: Board) -> Game {
+        let mut game = Game {
+            board: Arc::new(board),
+            current_piece: self.current_piece,
+            winner: None,
+            win_length: self.win_length,
+            ruleset: self.ruleset,
+            history: Vec::new(),
+            starting_piece: self.starting_piece,
+        };
+        game.update_winner();
+        game
+    }
+
+    // the position turned a quarter turn clockwise
+    pub fn rotated(&self) -> Game {
+        self.with_board(rotate90(&self.board))
+    }
+
+    // the position mirrored left to right
+    pub fn flipped_horizontal(&self) -> Game {
+        self.with_board(reflect(&self.board))
+    }
+
+    // the position mirrored top to bottom
+    pub fn flipped_vertical(&self) -> Game {
+        self.with_board(reflect(&rotate90(&rotate90(&self.board))))
+    }
+
+    // the position mirrored across the main diagonal, swapping rows and columns
+    pub fn transposed(&self) -> Game {
+        self.with_board(reflect(&rotate90(&self.board)))
+    }
+
+    // every cell with its contents, in row-major order
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), Option<Piece>)> + '_ {
+        let cols = self.board.num_columns();
+        self.board
+            .elements_row_major_iter()
+            .enumerate()
+            .map(move |(i, &piece)| ((i / cols, i % cols), piece))
+    }
+
+    // the cells holding a piece, in row-major order
+    pub fn occupied_cells(&self) -> impl Iterator<Item = ((usize, usize), Piece)> + '_ {
+        self.cells()
+            .filter_map(|(pos, piece)| piece.map(|piece| (pos, piece)))
+    }
+
+    // number of pieces on the board
+    pub fn move_count(&self) -> usize {
+        self.occupied_cells().count()
+    }
+
+    // number of empty cells, the same as valid_moves().len() until the game is finished
+    pub fn num_empty(&self) -> usize {
+        self.cells().filter(|(_, piece)| piece.is_none()).count()
+    }
+
+    // true when every cell holds a piece, which a won game need not reach
+    pub fn is_full(&self) -> bool {
+        self.num_empty() == 0
+    }
+
+    // 1-indexed number of the move about to be played
+    pub fn turn_number(&self) -> usize {
+        self.move_count() + 1
+    }
+
+    // true if other is a rotation or reflection of this position with the same player to
+    // move and the same rules
+    pub fn is_symmetric_to(&self, other: &Game) -> bool {
+        self.symmetry_to(other).is_some()
+    }
+
+    // hash of the canonical position, shared by games that are rotations or reflections
+    // of each other
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.canonical().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // the board at 2 bits per cell followed by a bit for the player to move, unique among
+    // boards of the same shape, panics if the board has more than 63 cells
+    pub fn state_key(&self) -> u128 {
+        assert!(
+            self.board.num_elements() <= 63,
+            "board too large for a state key"
+        );
+        let board = self
+            .board
+            .elements_row_major_iter()
+            .fold(0u128, |key, cell| {
+                key << 2
+                    | match cell {
+                        None => 0,
+                        Some(Piece::X) => 1,
+                        Some(Piece::O) => 2,
+                    }
+            });
+        board << 1 | (self.current_piece == Piece::O) as u128
+    }
+
+    pub fn canonicalize(self) -> CanonicalGame {
+        CanonicalGame(self)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.winner == Some(Winner::Tie)
+    }
+
+    // the piece that won, None for a tie or an unfinished game
+    pub fn victor(&self) -> Option<Piece> {
+        match self.winner {
+            Some(Winner::X) => Some(Piece::X),
+            Some(Winner::O) => Some(Piece::O),
+            Some(Winner::Tie) | None => None,
+        }
+    }
+
+    // cells of the line that won the game, None for a tie or an unfinished game
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        match self.winner {
+            Some(Winner::X) | Some(Winner::O) => completed_line(&self.board, self.win_length),
+            _ => None,
+        }
+    }
+
+    // the owner of every completed line, one entry per line in the order the lines are
+    // checked in, so a position with wins for both pieces reports both
+    pub fn all_winners(&self) -> Vec<Winner> {
+        completed_lines(&self.board, self.win_length)
+            .into_iter()
+            .filter_map(|line| self.board[line[0]])
+            .map(|piece| line_winner(piece, self.ruleset))
+            .collect()
+    }
+
+    // true if make_move would accept the move, without playing it
+    pub fn legal_to_place(&self, row: usize, col: usize) -> bool {
+        self.check_move(row, col).is_ok()
+    }
+
+    // the cell make_move would place the piece in, or the error it would give
+    fn check_move(&self, row: usize, col: usize) -> Result<(usize, usize), MoveError> {
+        if self.is_finished() {
+            return Err(MoveError::GameAlreadyOver);
+        }
+
+        if self.ruleset == Ruleset::Gravity {
+            if col >= self.board.num_columns() {
+                return Err(MoveError::InvalidPosition { row, col });
+            }
+            let row = self.drop_row(col).ok_or(MoveError::ColumnFull { col })?;
+            return Ok((row, col));
+        }
+
+        if row >= self.board.num_rows() || col >= self.board.num_columns() {
+            return Err(MoveError::InvalidPosition { row, col });
+        }
+
+        if let Some(piece) = self.board[(row, col)] {
+            return Err(MoveError::TileNotEmpty {
+                other_piece: piece,
+                row,
+                col,
+            });
+        }
+
+        Ok((row, col))
+    }
+
+    // lowest empty row of col, where a piece played there lands under gravity
+    fn drop_row(&self, col: usize) -> Option<usize> {
+        (0..self.board.num_rows())
+            .rev()
+            .find(|&row| self.board[(row, col)].is_none())
+    }
+
+    // under gravity row is ignored and the piece lands in the lowest empty cell of col
+    pub fn make_move(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        let (row, col) = self.check_move(row, col)?;
+
+        // modify the current state
+        Arc::make_mut(&mut self.board)[(row, col)] = Some(self.current_piece);
+        self.current_piece = self.current_piece.other();
+        self.winner = check_win_at(&self.board, self.win_length, self.ruleset, row, col);
+        self.history.push((row, col));
+        Ok(())
+    }
+
+    // the game after the move, leaving this one as it was
+    pub fn apply(&self, row: usize, col: usize) -> Result<Game, MoveError> {
+        let mut game = self.clone();
+        game.make_move(row, col)?;
+        Ok(game)
+    }
+
+    // make_move, also saying whether the move ended the game and how
+    pub fn try_move(&mut self, row: usize, col: usize) -> Result<MoveOutcome, MoveError> {
+        self.make_move(row, col)?;
+        Ok(match (self.victor(), self.is_tie()) {
+            (Some(piece), _) => MoveOutcome::Won(piece),
+            (None, true) => MoveOutcome::Tied,
+            (None, false) => MoveOutcome::Continued,
+        })
+    }
+
+    // clear the board back to the starting position, reusing its storage
+    pub fn reset(&mut self) {
+        self.current_piece = self.starting_piece;
+        let board = Arc::make_mut(&mut self.board);
+        for row in 0..board.num_rows() {
+            for col in 0..board.num_columns() {
+                board[(row, col)] = None;
+            }
+        }
+        self.winner = None;
+        self.history.clear();
+    }
+
+    // take back the last move
+    pub fn undo_move(&mut self) -> Result<(), MoveError> {
+        let (row, col) = self.history.pop().ok_or(MoveError::NothingToUndo)?;
+        Arc::make_mut(&mut self.board)[(row, col)] = None;
+        self.current_piece = self.current_piece.other();
+        self.update_winner();
+        Ok(())
+    }
+
+    // status of every row, column and diagonal window of win_length cells
+    pub fn line_status(&self) -> Vec<LineStatus> {
+        lines(&self.board, self.win_length)
+            .iter()
+            .map(|line| {
+                let mut pieces = line.iter().filter_map(|&pos| self.board[pos]);
+                match pieces.next() {
+                    None => LineStatus::Open,
+                    Some(first) if pieces.all(|piece| piece == first) => LineStatus::OwnedBy(first),
+                    Some(_) => LineStatus::Dead,
+                }
+            })
+            .collect()
+    }
+
+    // lines holding only piece minus lines holding only its opponent, with a completed
+    // line worth WON_LINE_SCORE instead of 1, a cheap evaluation for depth-limited search
+    pub fn heuristic_score(&self, piece: Piece) -> i32 {
+        lines(&self.board, self.win_length)
+            .iter()
+            .map(|line| {
+                let mut pieces = line.iter().filter_map(|&pos| self.board[pos]);
+                let owner = match pieces.next() {
+                    Some(first) if pieces.all(|p| p == first) => first,
+                    _ => return 0,
+                };
+                let score = if is_complete(&self.board, line) {
+                    WON_LINE_SCORE
+                } else {
+                    1
+                };
+                if owner == piece {
+                    score
+                } else {
+                    -score
+                }
+            })
+            .sum()
+    }
+
+    // true when every line already holds both pieces, so the game must end in a tie
+    // however the remaining cells are filled
+    pub fn is_dead_draw(&self) -> bool {
+        self.line_status()
+            .into_iter()
+            .all(|status| status == LineStatus::Dead)
+    }
+
+    // a legal move that wins the game for piece, whether or not it is piece's turn
+    pub fn winning_move(&self, piece: Piece) -> Option<(usize, usize)> {
+        self.winning_moves(piece).first().copied()
+    }
+
+    // every legal move that completes a line for piece and so wins it the game, none under
+    // misere where completing a line loses
+    pub fn winning_moves(&self, piece: Piece) -> Vec<(usize, usize)> {
+        if line_winner(piece, self.ruleset) != piece.into() {
+            return Vec::new();
+        }
+        self.valid_moves()
+            .into_iter()
+            .filter(|&cell| {
+                let mut board = Board::clone(&self.board);
+                board[cell] = Some(piece);
+                completes_line_at(&board, self.win_length, cell)
+            })
+            .collect()
+    }
+
+    // a cell where piece's opponent would complete a line, so piece should play there,
+    // against a fork this blocks only one of the threats
+    pub fn blocking_move(&self, piece: Piece) -> Option<(usize, usize)> {
+        self.winning_move(piece.other())
+    }
+
+    // empty cells where placing piece would leave a line through that cell one piece
+    // short of a win
+    pub fn threats(&self, piece: Piece) -> Vec<(usize, usize)> {
+        let lines = lines(&self.board, self.win_length);
+        self.valid_moves()
+            .into_iter()
+            .filter(|&cell| {
+                let mut board = Board::clone(&self.board);
+                board[cell] = Some(piece);
+                let through_cell: Vec<_> = lines
+                    .iter()
+                    .filter(|line| line.contains(&cell))
+                    .cloned()
+                    .collect();
+                !threat_cells(&board, &through_cell, piece).is_empty()
+            })
+            .collect()
+    }
+
+    // true if placing piece at (row, col) would give it two or more new winning cells for its
+    // next move on the lines through that cell, cells it already threatens don't count
+    pub fn creates_fork(&self, row: usize, col: usize, piece: Piece) -> bool {
+        if self.is_finished() || self.board.get(row, col) != Some(&None) {
+            return false;
+        }
+        let lines = lines(&self.board, self.win_length);
+        let before: HashSet<_> = threat_cells(&self.board, &lines, piece)
+            .into_iter()
+            .collect();
+        let mut board = Board::clone(&self.board);
+        board[(row, col)] = Some(piece);
+        let through_cell: Vec<_> = lines
+            .into_iter()
+            .filter(|line| line.contains(&(row, col)))
+            .collect();
+        let threats: HashSet<_> = threat_cells(&board, &through_cell, piece)
+            .into_iter()
+            .filter(|cell| !before.contains(cell))
+            .collect();
+        threats.len() >= 2
+    }
+
+    fn update_winner(&mut self) {
+        self.winner = to_winner(&self.board, self.win_length, self.ruleset);
+    }
+
+    // empty cells that can be played, none once the game is finished, under gravity only
+    // the lowest empty cell of each column
+    pub fn valid_moves(&self) -> Vec<(usize, usize)> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+        if self.ruleset == Ruleset::Gravity {
+            return (0..self.board.num_columns())
+                .filter_map(|col| self.drop_row(col).map(|row| (row, col)))
+                .collect();
+        }
+        self.cells()
+            .filter(|(_, piece)| piece.is_none())
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+}
+
+// a piece as accepted by Piece::try_from, or ' ' or '.' for an empty cell
+pub fn parse_cell(c: char) -> Result<Option<Piece>, ParseError> {
+    match c {
+        ' ' | '.' => Ok(None),
+        c => Piece::try_from(c).map(Some),
+    }
+}
+
+fn to_char(maybe_piece: &Option<Piece>) -> char {
+    match maybe_piece {
+        None => ' ',
+        Some(Piece::X) => 'X',
+        Some(Piece::O) => 'O',
+    }
+}
+
+// writes one cell of the grid built by Game::render_with
+pub trait CellRenderer {
+    fn render(&self, cell: Option<Piece>, row: usize, col: usize) -> String;
+}
+
+// the cells as Display writes them, a space for an empty cell
+pub struct PlainRenderer;
+
+impl CellRenderer for PlainRenderer {
+    fn render(&self, cell: Option<Piece>, _row: usize, _col: usize) -> String {
+        to_char(&cell).to_string()
+    }
+}
+
+// red for x and blue for o, reset afterwards so the grid lines stay uncolored
+#[cfg(feature = "color")]
+struct ColorRenderer;
+
+#[cfg(feature = "color")]
+impl CellRenderer for ColorRenderer {
+    fn render(&self, cell: Option<Piece>, _row: usize, _col: usize) -> String {
+        match cell {
+            None => String::from(" "),
+            Some(Piece::X) => String::from("\x1b[31mX\x1b[0m"),
+            Some(Piece::O) => String::from("\x1b[34mO\x1b[0m"),
+        }
+    }
+}
+
+impl Game {
+    // the board laid out as Display does, with each cell written by renderer
+    pub fn render_with(&self, renderer: &impl CellRenderer) -> String {
+        let separator = format!("\n{}\n", "-".repeat(2 * self.board.num_columns() - 1));
+        self.board
+            .rows_iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                cells
+                    .enumerate()
+                    .map(|(col, &cell)| renderer.render(cell, row, col))
+                    .join("|")
+            })
+            .join(&separator)
+    }
+
+    // the board alone drawn with box-drawing characters, one cell three characters wide
+    pub fn to_unicode_string(&self) -> String {
+        let cols = self.board.num_columns();
+        let border = |left: &str, middle: &str, right: &str| {
+            format!("{}{}{}", left, vec!["───"; cols].join(middle), right)
+        };
+        let rows = self
+            .board
+            .rows_iter()
+            .map(|row| {
+                format!(
+                    "│{}│",
+                    row.map(|cell| format!(" {} ", to_char(cell))).join("│")
+                )
+            })
+            .join(&format!("\n{}\n", border("├", "┼", "┤")));
+        format!(
+            "{}\n{}\n{}",
+            border("┌", "┬", "┐"),
+            rows,
+            border("└", "┴", "┘")
+        )
+    }
+
+    // same as Display but with the pieces colored by ansi escape codes, plain output when
+    // the NO_COLOR environment variable is set
+    #[cfg(feature = "color")]
+    pub fn to_colored_string(&self) -> String {
+        if matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty()) {
+            return self.to_string();
+        }
+        format!(
+            "{}\nWinner: {:?}",
+            self.render_with(&ColorRenderer),
+            self.winner
+        )
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display_board = self.render_with(&PlainRenderer);
+        write!(f, "{}\nWinner: {:?}", display_board, self.winner)
+    }
+}
+
+// parses the format written by Display, the trailing winner line is optional
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s
+            .lines()
+            .filter(|line| !line.starts_with("Winner:") && !line.chars().all(|c| c == '-'))
+            .enumerate()
+            .map(|(row, line)| {
+                line.split('|')
+                    .enumerate()
+                    .map(|(col, cell)| {
+                        let cell = cell.trim();
+                        let mut chars = cell.chars();
+                        match (chars.next(), chars.next()) {
+                            (None, _) => Some(None),
+                            (Some(c), None) => parse_cell(c).ok(),
+                            _ => None,
+                        }
+                        .ok_or_else(|| ParseError::InvalidCell {
+                            row,
+                            col,
+                            cell: cell.to_string(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Game::try_from(rows)
+    }
+}
+
+// build a game from rows of cells, inferring whose turn it is from the piece counts
+impl TryFrom<Vec<Vec<Option<Piece>>>> for Game {
+    type Error = ParseError;
+
+    fn try_from(rows: Vec<Vec<Option<Piece>>>) -> Result<Self, Self::Error> {
+        if rows.is_empty() || rows[0].is_empty() {
+            return Err(ParseError::EmptyBoard);
+        }
+
+        let cols = rows[0].len();
+        let mut board = Array2D::filled_with(None, rows.len(), cols);
+        for (row, cells) in rows.iter().enumerate() {
+            if cells.len() != cols {
+                return Err(ParseError::InconsistentDimensions {
+                    row,
+                    expected: cols,
+                    found: cells.len(),
+                });
+            }
+            for (col, &cell) in cells.iter().enumerate() {
+                board[(row, col)] = cell;
+            }
+        }
+
+        Game::from_board(board)
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> TryFrom<[[Option<Piece>; COLS]; ROWS]> for Game {
+    type Error = ParseError;
+
+    fn try_from(rows: [[Option<Piece>; COLS]; ROWS]) -> Result<Self, Self::Error> {
+        Game::try_from(rows.iter().map(|row| row.to_vec()).collect::<Vec<_>>())
+    }
+}
+
+// read a move written as a column letter and a 1-indexed row number, such as b2 or 2b
+pub fn parse_move(input: &str, board_size: usize) -> Result<(usize, usize), ParseError> {
+    let input = input.trim().to_ascii_lowercase();
+    let invalid = || ParseError::InvalidNotation {
+        input: input.clone(),
+    };
+    let letters: String = input.chars().filter(char::is_ascii_lowercase).collect();
+    let digits: String = input.chars().filter(char::is_ascii_digit).collect();
+    if letters.len() != 1
+        || digits.is_empty()
+        || (input != format!("{}{}", letters, digits) && input != format!("{}{}", digits, letters))
+    {
+        return Err(invalid());
+    }
+
+    let col = (letters.as_bytes()[0] - b'a') as usize;
+    let row = digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|row| row.checked_sub(1))
+        .ok_or_else(invalid)?;
+    if row >= board_size || col >= board_size {
+        return Err(ParseError::CellOutOfRange { row, col });
+    }
+    Ok((row, col))
+}
+
+// write a move the way parse_move reads it
+pub fn move_to_notation((row, col): (usize, usize)) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, row + 1)
+}
+
+pub fn next_games(game: &Game) -> Vec<Game> {
+    game.valid_moves()
+        .into_iter()
+        .map(|(row, col)| game.apply(row, col).expect("valid move was rejected"))
+        .collect()
+}
+
+// the game after each of moves is played on a new game, ending before the first illegal
+// move
+pub fn replay(moves: &[(usize, usize)]) -> impl Iterator<Item = Game> + '_ {
+    let mut game = Game::new();
+    moves.iter().map_while(move |&(row, col)| {
+        game.make_move(row, col).ok()?;
+        Some(game.clone())
+    })
+}
+
+// every distinct finished game reachable from game, each yielded once
+pub fn terminal_games(game: &Game) -> impl Iterator<Item = Game> {
+    let mut stack = vec![game.clone()];
+    let mut seen = HashSet::new();
+    std::iter::from_fn(move || {
+        while let Some(game) = stack.pop() {
+            if !seen.insert(game.clone()) {
+                continue;
+            }
+            if game.is_finished() {
+                return Some(game);
+            }
+            stack.extend(next_games(&game));
+        }
+        None
+    })
+}
+
+// number of complete games from this position won by (x, o, tied), found depth first
+// without keeping a tree around
+pub fn count_outcomes(game: &Game) -> (u64, u64, u64) {
+    match game.winner {
+        Some(Winner::X) => (1, 0, 0),
+        Some(Winner::O) => (0, 1, 0),
+        Some(Winner::Tie) => (0, 0, 1),
+        None => next_games(game)
+            .iter()
+            .map(count_outcomes)
+            .fold((0, 0, 0), |sums, counts| {
+                (sums.0 + counts.0, sums.1 + counts.1, sums.2 + counts.2)
+            }),
+    }
+}
+
+// number of complete games that can be played out from this position, the number of
+// leaves GameTree::from would build
+pub fn count_games_from(game: &Game) -> u64 {
+    let (x_wins, o_wins, ties) = count_outcomes(game);
+    x_wins + o_wins + ties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_by_four_row_win() {
+        let mut game = Game::with_size(4);
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner, None);
+        game.make_move(0, 3).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn four_by_four_diagonal_win() {
+        let mut game = Game::with_size(4);
+        for (row, col) in [(0, 0), (0, 1), (1, 1), (0, 2), (2, 2), (0, 3), (3, 3)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn winning_line_reports_the_cells_of_the_win() {
+        let game = Game::from_moves(&[(0, 2), (0, 0), (1, 1), (0, 1), (2, 0)]).unwrap();
+        assert_eq!(game.winning_line(), Some(vec![(0, 2), (1, 1), (2, 0)]));
+        let game = Game::from_moves(&[(0, 0), (0, 1), (1, 1), (0, 2), (2, 2)]).unwrap();
+        assert_eq!(game.winning_line(), Some(vec![(0, 0), (1, 1), (2, 2)]));
+        assert_eq!(Game::new().winning_line(), None);
+    }
+
+    #[test]
+    fn move_errors_describe_themselves() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        let err = game.make_move(1, 1).unwrap_err();
+        assert_eq!(err.to_string(), "tile (1,1) already occupied by X");
+        let err = game.make_move(3, 0).unwrap_err();
+        assert_eq!(err.to_string(), "position (3,0) is outside the board");
+        let err: Box<dyn std::error::Error> = Box::new(MoveError::GameAlreadyOver);
+        assert_eq!(err.to_string(), "game is already over");
+    }
+
+    #[test]
+    fn display_round_trips_through_from_str() {
+        let game = Game::from_moves(&[(0, 0), (1, 1), (2, 2)]).unwrap();
+        assert_eq!(game.to_string().parse::<Game>().unwrap(), game);
+
+        let mut o_first = Game::with_starting_piece(Piece::O);
+        o_first.make_move(1, 1).unwrap();
+        assert_eq!(o_first.to_string().parse::<Game>().unwrap(), o_first);
+    }
+
+    #[test]
+    fn from_str_reads_display_format() {
+        let game: Game = "X|O| \n-----\n |X| \n-----\nO| |X".parse().unwrap();
+        assert_eq!(game.piece_at(0, 1), Some(Piece::O));
+        assert_eq!(game.winner, Some(Winner::X));
+        assert_eq!(
+            "X|X| \n-----\n |X| \n-----\n | | "
+                .parse::<Game>()
+                .unwrap_err(),
+            ParseError::InvalidPieceCount {
+                x_count: 3,
+                o_count: 0
+            }
+        );
+        assert!(matches!(
+            "X|O\n-----\n |X| ".parse::<Game>(),
+            Err(ParseError::InconsistentDimensions { row: 1, .. })
+        ));
+        assert!(matches!(
+            "X|Q| ".parse::<Game>(),
+            Err(ParseError::InvalidCell { .. })
+        ));
+    }
+
+    #[test]
+    fn accessors_follow_the_moves() {
+        let mut game = Game::new();
+        assert_eq!(game.current_piece(), Piece::X);
+        assert!(game
+            .board()
+            .elements_row_major_iter()
+            .all(|cell| cell.is_none()));
+        game.make_move(0, 2).unwrap();
+        assert_eq!(game.current_piece(), Piece::O);
+        assert_eq!(game.board()[(0, 2)], Some(Piece::X));
+    }
+
+    #[test]
+    fn undoing_every_move_gets_back_to_a_new_game() {
+        let mut game = Game::new();
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert!(game.is_finished());
+        for _ in 0..5 {
+            game.undo_move().unwrap();
+        }
+        assert!(matches!(game.undo_move(), Err(MoveError::NothingToUndo)));
+        assert_eq!(game.board(), Game::new().board());
+        assert_eq!(game, Game::new());
+    }
+
+    #[test]
+    fn all_eight_transforms_share_a_canonical_form() {
+        let game = Game::from_moves(&[(0, 1), (2, 2), (2, 0)]).unwrap();
+        let canonical = game.canonical();
+        let mut board = game.board().clone();
+        let mut boards = HashSet::new();
+        for _ in 0..4 {
+            for transformed in [board.clone(), reflect(&board)] {
+                assert_eq!(
+                    Game::from_board(transformed.clone()).unwrap().canonical(),
+                    canonical
+                );
+                boards.insert(transformed);
+            }
+            board = rotate90(&board);
+        }
+        // the position has no symmetry of its own, so the transforms are all different
+        assert_eq!(boards.len(), 8);
+        assert_ne!(Game::from_moves(&[(1, 1)]).unwrap().canonical(), canonical);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let game = Game::from_moves(&[(0, 0), (1, 1), (0, 1)]).unwrap();
+        let json = serde_json::to_string(&game).unwrap();
+        let loaded: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.current_piece(), Piece::O);
+        assert_eq!(loaded.winner, None);
+
+        let mut loaded = loaded;
+        loaded.undo_move().unwrap();
+        assert_eq!(loaded, Game::from_moves(&[(0, 0), (1, 1)]).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_that_breaks_the_game_is_rejected() {
+        let game = Game::from_moves(&[(0, 0), (1, 1)]).unwrap();
+        let json = serde_json::to_value(&game).unwrap();
+        let load = |field: &str, value: serde_json::Value| {
+            let mut json = json.clone();
+            json[field] = value;
+            serde_json::from_value::<Game>(json)
+        };
+        assert!(load("win_length", 0.into()).is_err());
+        assert!(load("win_length", 4.into()).is_err());
+        assert!(load("history", serde_json::json!([[0, 0], [7, 7]])).is_err());
+        assert!(load("history", serde_json::json!([[1, 1], [0, 0]])).is_err());
+
+        // a forged winner is replaced by the real one
+        let loaded = load("winner", serde_json::json!("X")).unwrap();
+        assert_eq!(loaded.winner, None);
+    }
+
+    #[test]
+    fn four_in_a_row_on_five_by_five() {
+        // diagonal windows away from the corners, in both directions
+        let game = Game::with_rules(5, 4);
+        let down_right = [(1, 0), (0, 0), (2, 1), (0, 1), (3, 2), (0, 2), (4, 3)];
+        let down_left = [(1, 4), (0, 0), (2, 3), (0, 1), (3, 2), (0, 2), (4, 1)];
+        for moves in [down_right, down_left] {
+            let mut game = game.clone();
+            for (row, col) in moves {
+                game.make_move(row, col).unwrap();
+            }
+            assert_eq!(game.winner, Some(Winner::X));
+            assert_eq!(game.winning_line().unwrap().len(), 4);
+        }
+
+        // three in a row isn't enough
+        let mut game = game.clone();
+        for (row, col) in [(1, 1), (0, 0), (1, 2), (0, 1), (1, 3)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner, None);
+        game.make_move(4, 4).unwrap();
+        game.make_move(1, 4).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn move_count_only_counts_moves_made() {
+        let mut game = Game::new();
+        assert_eq!((game.move_count(), game.turn_number()), (0, 1));
+        for (count, (row, col)) in [(1, 1), (0, 0), (2, 2)].into_iter().enumerate() {
+            game.make_move(row, col).unwrap();
+            assert_eq!(game.move_count(), count + 1);
+            assert!(game.make_move(row, col).is_err());
+            assert_eq!(game.move_count(), count + 1);
+        }
+        assert_eq!(game.turn_number(), 4);
+    }
+
+    #[test]
+    fn there_are_958_finished_positions() {
+        assert_eq!(terminal_games(&Game::new()).count(), 958);
+        assert!(terminal_games(&Game::new()).all(|game| game.is_finished()));
+        let finished = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(
+            terminal_games(&finished).collect::<Vec<_>>(),
+            vec![finished]
+        );
+    }
+
+    #[test]
+    fn pieces_and_winners_display_as_letters() {
+        assert_eq!(Piece::X.to_string(), "X");
+        assert_eq!(Piece::O.to_string(), "O");
+        assert_eq!(Winner::X.to_string(), "X");
+        assert_eq!(Winner::O.to_string(), "O");
+        assert_eq!(Winner::Tie.to_string(), "Tie");
+    }
+
+    #[test]
+    fn canonical_hash_is_shared_by_symmetric_games() {
+        let corner = Game::from_moves(&[(0, 0), (1, 1)]).unwrap();
+        for other in [[(0, 2), (1, 1)], [(2, 0), (1, 1)], [(2, 2), (1, 1)]] {
+            assert_eq!(
+                Game::from_moves(&other).unwrap().canonical_hash(),
+                corner.canonical_hash()
+            );
+        }
+        let edge = Game::from_moves(&[(0, 1), (1, 1)]).unwrap();
+        assert_ne!(edge.canonical_hash(), corner.canonical_hash());
+    }
+
+    #[test]
+    fn from_moves_replays_until_an_illegal_move() {
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+        assert!(matches!(
+            Game::from_moves(&[(0, 0), (0, 0), (1, 1)]),
+            Err(MoveError::TileNotEmpty {
+                other_piece: Piece::X,
+                row: 0,
+                col: 0
+            })
+        ));
+        assert!(matches!(
+            Game::from_moves(&[(0, 0), (3, 1)]),
+            Err(MoveError::InvalidPosition { row: 3, col: 1 })
+        ));
+        assert!(matches!(
+            Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (2, 2)]),
+            Err(MoveError::GameAlreadyOver)
+        ));
+    }
+
+    #[test]
+    fn opposite_corners_set_up_a_fork() {
+        // x holds opposite corners around o's center, either free corner threatens twice
+        let game = Game::from_compact("X...O...X").unwrap();
+        assert!(game.creates_fork(0, 2, Piece::X));
+        assert!(game.creates_fork(2, 0, Piece::X));
+        assert!(!game.creates_fork(0, 1, Piece::X));
+        assert!(!game.creates_fork(0, 0, Piece::X));
+        assert_eq!(
+            game.threats(Piece::X),
+            vec![(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]
+        );
+        assert!(Game::new().threats(Piece::X).is_empty());
+    }
+
+    #[test]
+    fn a_threat_already_on_the_board_is_no_fork() {
+        // x already threatens (0, 2), and (2, 0) only adds the threat at (1, 0)
+        let game = Game::from_compact("XX..O....").unwrap();
+        assert!(!game.creates_fork(2, 0, Piece::X));
+        // (0, 2) would threaten (2, 2) and (2, 0), but o has already won
+        let won = Game::from_compact("XX.OOO.X.").unwrap();
+        assert!(!won.creates_fork(0, 2, Piece::O));
+    }
+
+    #[test]
+    fn wide_board_wins() {
+        let play = |moves: &[(usize, usize)]| {
+            let mut game = Game::with_dimensions(3, 5, 3);
+            for &(row, col) in moves {
+                game.make_move(row, col).unwrap();
+            }
+            game
+        };
+        let game = play(&[(1, 2), (0, 0), (1, 3), (2, 0), (1, 4)]);
+        assert_eq!(game.winning_line(), Some(vec![(1, 2), (1, 3), (1, 4)]));
+        // diagonals starting away from the corners
+        let game = play(&[(0, 1), (0, 0), (1, 2), (1, 0), (2, 3)]);
+        assert_eq!(game.winning_line(), Some(vec![(0, 1), (1, 2), (2, 3)]));
+        let game = play(&[(0, 3), (0, 0), (1, 2), (1, 0), (2, 1)]);
+        assert_eq!(game.winning_line(), Some(vec![(0, 3), (1, 2), (2, 1)]));
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn misere_gives_the_win_to_the_other_piece() {
+        let play_misere = |moves: &[(usize, usize)]| {
+            let mut game = Game::new().with_ruleset(Ruleset::Misere).unwrap();
+            for &(row, col) in moves {
+                game.make_move(row, col).unwrap();
+            }
+            game
+        };
+        let moves = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let normal = Game::from_moves(&moves).unwrap();
+        let misere = play_misere(&moves);
+        assert_eq!(normal.board(), misere.board());
+        assert_eq!(normal.winner, Some(Winner::X));
+        assert_eq!(misere.winner, Some(Winner::O));
+        // a full board without a line is a tie either way
+        let moves = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 1),
+            (1, 0),
+            (1, 2),
+            (2, 1),
+            (2, 0),
+            (2, 2),
+        ];
+        assert_eq!(Game::from_moves(&moves).unwrap().winner, Some(Winner::Tie));
+        assert_eq!(play_misere(&moves).winner, Some(Winner::Tie));
+        // the rules can't change once a piece is down
+        let started = Game::from_moves(&moves[..1]).unwrap();
+        assert!(matches!(
+            started.with_ruleset(Ruleset::Misere),
+            Err(MoveError::GameStarted)
+        ));
+    }
+
+    #[test]
+    fn valid_moves_are_the_empty_cells() {
+        assert_eq!(Game::new().valid_moves().len(), 9);
+        let game = Game::from_moves(&[(1, 1), (0, 0), (2, 1)]).unwrap();
+        let moves = game.valid_moves();
+        assert_eq!(moves.len(), 6);
+        assert!(!moves.contains(&(1, 1)) && !moves.contains(&(0, 0)) && !moves.contains(&(2, 1)));
+        assert!(Game::from_compact("XOXXOOOXX")
+            .unwrap()
+            .valid_moves()
+            .is_empty());
+    }
+
+    #[test]
+    fn cells_cover_the_board() {
+        let mut game = Game::with_dimensions(3, 4, 3);
+        for (row, col) in [(0, 3), (2, 0), (1, 1)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.cells().count(), 3 * 4);
+        assert_eq!(game.cells().nth(3), Some(((0, 3), Some(Piece::X))));
+        assert_eq!(game.occupied_cells().count(), game.move_count());
+        assert_eq!(
+            game.occupied_cells().collect::<Vec<_>>(),
+            vec![((0, 3), Piece::X), ((1, 1), Piece::X), ((2, 0), Piece::O)]
+        );
+    }
+
+    #[test]
+    fn mirrored_openings_are_symmetric() {
+        let corner = Game::from_moves(&[(0, 0)]).unwrap();
+        let mirrored = Game::from_moves(&[(0, 2)]).unwrap();
+        assert!(corner.is_symmetric_to(&mirrored));
+        assert!(corner.is_symmetric_to(&corner));
+        assert!(!corner.is_symmetric_to(&Game::from_moves(&[(1, 1)]).unwrap()));
+        assert!(!corner.is_symmetric_to(&Game::from_moves(&[(0, 1)]).unwrap()));
+        // o's reply in the opposite corner or next to x are different positions
+        let opposite = Game::from_moves(&[(0, 0), (2, 2)]).unwrap();
+        let adjacent = Game::from_moves(&[(0, 0), (0, 1)]).unwrap();
+        assert!(opposite.is_symmetric_to(&Game::from_moves(&[(2, 0), (0, 2)]).unwrap()));
+        assert!(!opposite.is_symmetric_to(&adjacent));
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let game = Game::from_moves(&[(0, 0), (0, 1), (1, 1), (2, 1)]).unwrap();
+        assert_eq!(game.to_compact(), "XO..X..O.");
+        assert_eq!(Game::from_compact(&game.to_compact()).unwrap(), game);
+
+        let mut wide = Game::with_dimensions(2, 8, 2);
+        wide.make_move(1, 7).unwrap();
+        assert_eq!(wide.to_compact(), "......../.......X");
+        let parsed = Game::from_compact(&wide.to_compact()).unwrap();
+        assert_eq!(parsed, wide);
+        assert_eq!(parsed.board().num_columns(), 8);
+    }
+
+    #[test]
+    fn from_compact_rejects_bad_shapes() {
+        assert_eq!(
+            Game::from_compact("X........."),
+            Err(ParseError::InvalidLength { length: 10 })
+        );
+        assert_eq!(Game::from_compact(""), Err(ParseError::EmptyBoard));
+        assert_eq!(
+            Game::from_compact("..../..."),
+            Err(ParseError::InconsistentDimensions {
+                row: 1,
+                expected: 4,
+                found: 3
+            })
+        );
+        assert!(matches!(
+            Game::from_compact("X?......."),
+            Err(ParseError::InvalidCell { row: 0, col: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn reset_game_can_be_replayed() {
+        let moves = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let mut game = Game::new();
+        for _ in 0..3 {
+            for (row, col) in moves {
+                game.make_move(row, col).unwrap();
+            }
+            assert_eq!(game, Game::from_moves(&moves).unwrap());
+            game.reset();
+            assert_eq!(game, Game::new());
+        }
+    }
+
+    #[test]
+    fn four_by_four_anti_diagonal_win() {
+        let moves = [(0, 3), (0, 0), (1, 2), (0, 1), (2, 1), (0, 2), (3, 0)];
+        let mut game = Game::with_size(4);
+        for (row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner, Some(Winner::X));
+        assert_eq!(
+            game.winning_line(),
+            Some(vec![(0, 3), (1, 2), (2, 1), (3, 0)])
+        );
+
+        let moves = [
+            (0, 0),
+            (0, 3),
+            (0, 1),
+            (1, 2),
+            (1, 0),
+            (2, 1),
+            (3, 3),
+            (3, 0),
+        ];
+        let mut game = Game::with_size(4);
+        for (row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner, Some(Winner::O));
+    }
+
+    #[test]
+    fn parse_move_reads_notation_in_any_case() {
+        assert_eq!(parse_move("b2", 3), Ok((1, 1)));
+        assert_eq!(parse_move("C1", 3), Ok((0, 2)));
+        assert_eq!(parse_move(" 3a ", 3), Ok((2, 0)));
+        assert_eq!(parse_move("d4", 4), Ok((3, 3)));
+        assert_eq!(move_to_notation((2, 1)), "b3");
+        assert_eq!(
+            parse_move("d1", 3),
+            Err(ParseError::CellOutOfRange { row: 0, col: 3 })
+        );
+        assert_eq!(
+            parse_move("a4", 3),
+            Err(ParseError::CellOutOfRange { row: 3, col: 0 })
+        );
+        for input in ["a0", "b", "22", "a1b", "a-1", ""] {
+            assert!(
+                matches!(
+                    parse_move(input, 3),
+                    Err(ParseError::InvalidNotation { .. })
+                ),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn outcome_for_every_winner_and_piece() {
+        let cases = [
+            (Winner::X, Piece::X, Outcome::Win),
+            (Winner::X, Piece::O, Outcome::Loss),
+            (Winner::O, Piece::X, Outcome::Loss),
+            (Winner::O, Piece::O, Outcome::Win),
+            (Winner::Tie, Piece::X, Outcome::Draw),
+            (Winner::Tie, Piece::O, Outcome::Draw),
+        ];
+        for (winner, piece, outcome) in cases {
+            assert_eq!(outcome_for(winner, piece), outcome);
+        }
+    }
+
+    #[test]
+    fn count_outcomes_of_every_game() {
+        assert_eq!(count_outcomes(&Game::new()), (131184, 77904, 46080));
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(count_outcomes(&won), (1, 0, 0));
+    }
+
+    #[test]
+    fn game_from_an_array_literal() {
+        const X: Option<Piece> = Some(Piece::X);
+        const O: Option<Piece> = Some(Piece::O);
+        let game = Game::try_from([[X, O, None], [None, X, None], [None, None, None]]).unwrap();
+        assert_eq!(game, Game::from_compact("XO..X....").unwrap());
+        assert_eq!(game.current_piece(), Piece::O);
+        let game = Game::try_from([[X, X, X, None], [O, O, None, None], [None; 4]]).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+        assert_eq!(
+            Game::try_from([[X, X], [X, None]]).unwrap_err(),
+            ParseError::InvalidPieceCount {
+                x_count: 3,
+                o_count: 0
+            }
+        );
+        assert_eq!(
+            Game::try_from(vec![vec![X, O], vec![None]]).unwrap_err(),
+            ParseError::InconsistentDimensions {
+                row: 1,
+                expected: 2,
+                found: 1
+            }
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn colored_output_is_display_with_escape_codes() {
+        // drop every "\x1b[...m" sequence
+        let strip = |s: &str| {
+            let mut plain = String::new();
+            let mut chars = s.chars();
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.by_ref().find(|&c| c == 'm');
+                } else {
+                    plain.push(c);
+                }
+            }
+            plain
+        };
+        let game = Game::from_moves(&[(0, 0), (1, 1), (2, 2)]).unwrap();
+        let colored = game.to_colored_string();
+        if std::env::var_os("NO_COLOR").is_none() {
+            assert!(colored.contains('\x1b'));
+        }
+        assert_eq!(strip(&colored), game.to_string());
+    }
+
+    #[test]
+    fn transforms_of_a_position_hash_to_one_entry() {
+        let game = Game::from_moves(&[(0, 1), (2, 2), (2, 0)]).unwrap();
+        let set: HashSet<_> = Symmetry::all()
+            .map(|symmetry| game.transformed(symmetry).canonicalize())
+            .collect();
+        assert_eq!(set.len(), 1);
+        let set: HashSet<_> = [(0, 0), (0, 1), (1, 1), (2, 2)]
+            .into_iter()
+            .map(|mv| Game::from_moves(&[mv]).unwrap().canonicalize())
+            .collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn incremental_winner_agrees_with_a_full_rescan() {
+        fn check_from(game: &Game, seen: &mut HashSet<Game>) {
+            if !seen.insert(game.clone()) {
+                return;
+            }
+            assert_eq!(
+                game.winner,
+                to_winner(game.board(), game.win_length, game.ruleset),
+                "{}",
+                game
+            );
+            for child in next_games(game) {
+                check_from(&child, seen);
+            }
+        }
+        for game in [
+            Game::new(),
+            Game::new().with_ruleset(Ruleset::Misere).unwrap(),
+            Game::with_dimensions(3, 4, 3),
+        ] {
+            check_from(&game, &mut HashSet::new());
+        }
+    }
+
+    #[test]
+    fn num_empty_and_is_full() {
+        assert_eq!(Game::new().num_empty(), 9);
+        assert!(!Game::new().is_full());
+        let game = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        assert_eq!(game.num_empty(), 7);
+        // a game won before the board fills up isn't full
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(won.num_empty(), 4);
+        assert!(!won.is_full());
+        let tie = Game::from_compact("XOXXOOOXX").unwrap();
+        assert_eq!(tie.num_empty(), 0);
+        assert!(tie.is_full());
+    }
+
+    #[test]
+    fn legal_to_place_for_each_reason() {
+        let game = Game::from_moves(&[(1, 1)]).unwrap();
+        assert!(game.legal_to_place(0, 0));
+        assert!(!game.legal_to_place(1, 1));
+        assert!(!game.legal_to_place(3, 0));
+        assert!(!game.legal_to_place(0, 3));
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert!(!won.legal_to_place(2, 2));
+        let mut gravity = Game::with_dimensions(2, 3, 2)
+            .with_ruleset(Ruleset::Gravity)
+            .unwrap();
+        gravity.make_move(0, 0).unwrap();
+        gravity.make_move(0, 0).unwrap();
+        assert!(!gravity.legal_to_place(0, 0));
+        assert!(gravity.legal_to_place(0, 1));
+    }
+
+    #[test]
+    fn symmetries_compose() {
+        let game = Game::from_moves(&[(0, 1), (2, 2), (1, 0)]).unwrap();
+        for a in Symmetry::all() {
+            for b in Symmetry::all() {
+                assert_eq!(
+                    a.after(b).board(game.board()),
+                    a.board(&b.board(game.board()))
+                );
+                let cell = b.cell((0, 1), 3, 3);
+                assert_eq!(a.after(b).cell((0, 1), 3, 3), a.cell(cell, 3, 3));
+            }
+            let moved = game.transformed(a);
+            for ((row, col), piece) in game.occupied_cells() {
+                assert_eq!(moved[a.cell((row, col), 3, 3)], Some(piece));
+            }
+            let found = game.symmetry_to(&moved).unwrap();
+            assert_eq!(&found.board(game.board()), moved.board());
+        }
+    }
+
+    #[test]
+    fn gravity_stacks_pieces_and_finds_vertical_fours() {
+        let mut game = Game::with_dimensions(6, 7, 4)
+            .with_ruleset(Ruleset::Gravity)
+            .unwrap();
+        game.make_move(0, 3).unwrap();
+        game.make_move(0, 3).unwrap();
+        assert_eq!(game.piece_at(5, 3), Some(Piece::X));
+        assert_eq!(game.piece_at(4, 3), Some(Piece::O));
+        assert_eq!(game.piece_at(0, 3), None);
+        assert!(game.valid_moves().contains(&(3, 3)));
+
+        // x drops four into column 0 while o plays column 1
+        let mut game = Game::with_dimensions(6, 7, 4)
+            .with_ruleset(Ruleset::Gravity)
+            .unwrap();
+        for col in [0, 1, 0, 1, 0, 1] {
+            game.make_move(0, col).unwrap();
+        }
+        assert_eq!(game.winner, None);
+        game.make_move(0, 0).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+        assert_eq!(
+            game.winning_line(),
+            Some(vec![(2, 0), (3, 0), (4, 0), (5, 0)])
+        );
+
+        let mut full = Game::with_dimensions(2, 3, 2)
+            .with_ruleset(Ruleset::Gravity)
+            .unwrap();
+        full.make_move(0, 0).unwrap();
+        full.make_move(0, 0).unwrap();
+        assert!(matches!(
+            full.make_move(0, 0),
+            Err(MoveError::ColumnFull { col: 0 })
+        ));
+    }
+
+    #[test]
+    fn state_keys_are_unique_per_position() {
+        // the 958 different finished positions get 958 different keys
+        let keys: HashSet<_> = terminal_games(&Game::new())
+            .map(|game| game.state_key())
+            .collect();
+        assert_eq!(keys.len(), 958);
+        let corner = Game::from_moves(&[(0, 0)]).unwrap();
+        let mirrored = Game::from_moves(&[(0, 2)]).unwrap();
+        assert_ne!(corner.state_key(), mirrored.state_key());
+        assert_eq!(
+            corner.canonical().state_key(),
+            mirrored.canonical().state_key()
+        );
+        // the player to move is part of the key
+        assert_ne!(
+            Game::new().state_key(),
+            Game::with_starting_piece(Piece::O).state_key()
+        );
+    }
+
+    #[test]
+    fn starting_piece_o_moves_first() {
+        let mut game = Game::with_starting_piece(Piece::O);
+        assert_eq!(game.current_piece(), Piece::O);
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.piece_at(1, 1), Some(Piece::O));
+        assert_eq!(game.current_piece(), Piece::X);
+    }
+
+    #[test]
+    fn reset_restores_starting_piece() {
+        let mut game = Game::with_starting_piece(Piece::O);
+        game.make_move(0, 0).unwrap();
+        game.reset();
+        assert_eq!(game, Game::with_starting_piece(Piece::O));
+
+        let mut game = Game::from_setup(&[((0, 0), Piece::X)], Piece::X).unwrap();
+        game.reset();
+        assert_eq!(game.current_piece(), Piece::X);
+        assert_eq!(game.move_count(), 0);
+    }
+
+    #[test]
+    fn tie_has_no_victor() {
+        let tie = Game::from_compact("XOXXOOOXX").unwrap();
+        assert!(tie.is_tie());
+        assert_eq!(tie.victor(), None);
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert!(!won.is_tie());
+        assert_eq!(won.victor(), Some(Piece::X));
+        assert!(!Game::new().is_tie());
+        assert_eq!(Game::new().victor(), None);
+    }
+
+    #[test]
+    fn line_status_open_owned_and_dead() {
+        let statuses = Game::new().line_status();
+        assert_eq!(statuses.len(), 8);
+        assert!(statuses.iter().all(|&status| status == LineStatus::Open));
+
+        // x and o share the top row, x alone holds the left column and main diagonal
+        let game = Game::from_moves(&[(0, 0), (0, 1)]).unwrap();
+        let statuses: Vec<_> = lines(game.board(), 3)
+            .into_iter()
+            .zip(game.line_status())
+            .collect();
+        let status_of =
+            |line: Vec<(usize, usize)>| statuses.iter().find(|(l, _)| *l == line).unwrap().1;
+        assert_eq!(status_of(vec![(0, 0), (0, 1), (0, 2)]), LineStatus::Dead);
+        assert_eq!(
+            status_of(vec![(0, 0), (1, 0), (2, 0)]),
+            LineStatus::OwnedBy(Piece::X)
+        );
+        assert_eq!(
+            status_of(vec![(0, 1), (1, 1), (2, 1)]),
+            LineStatus::OwnedBy(Piece::O)
+        );
+        assert_eq!(status_of(vec![(1, 0), (1, 1), (1, 2)]), LineStatus::Open);
+    }
+
+    #[test]
+    fn count_games_from_root_and_mid_game() {
+        assert_eq!(count_games_from(&Game::new()), 255168);
+        // fewer than the 7! orders of the empty cells, since many games end early
+        let game = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        assert_eq!(count_games_from(&game), 3198);
+        let finished = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(count_games_from(&finished), 1);
+    }
+
+    #[test]
+    fn neighbors_stay_on_the_board() {
+        let game = Game::from_moves(&[(1, 1)]).unwrap();
+        assert_eq!(
+            game.neighbors(0, 0),
+            vec![((0, 1), None), ((1, 0), None), ((1, 1), Some(Piece::X))]
+        );
+        assert_eq!(game.neighbors(0, 1).len(), 5);
+        assert_eq!(game.neighbors(1, 1).len(), 8);
+        assert_eq!(game.neighbors(2, 2).len(), 3);
+    }
+
+    #[test]
+    fn replay_yields_a_game_per_move() {
+        let moves = [(1, 1), (0, 0), (2, 2), (0, 2)];
+        let games: Vec<_> = replay(&moves).collect();
+        assert_eq!(games.len(), moves.len());
+        assert_eq!(games.last(), Some(&Game::from_moves(&moves).unwrap()));
+        assert_eq!(games[0].piece_at(1, 1), Some(Piece::X));
+        // stops before the first illegal move
+        assert_eq!(replay(&[(1, 1), (0, 0), (1, 1), (2, 2)]).count(), 2);
+    }
+
+    #[test]
+    fn winning_move_finds_the_only_win() {
+        // x has two in the top row, o two in the middle
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        assert_eq!(game.winning_move(Piece::X), Some((0, 2)));
+        assert_eq!(game.winning_move(Piece::O), Some((1, 2)));
+        let (row, col) = game.winning_move(Piece::X).unwrap();
+        assert_eq!(game.apply(row, col).unwrap().winner, Some(Winner::X));
+
+        let game = Game::from_moves(&[(0, 0), (1, 1)]).unwrap();
+        assert_eq!(game.winning_move(Piece::X), None);
+        assert_eq!(game.winning_move(Piece::O), None);
+    }
+
+    #[test]
+    fn completing_a_line_isnt_a_win_under_misere() {
+        let mut game = Game::new().with_ruleset(Ruleset::Misere).unwrap();
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winning_move(Piece::X), None);
+        assert_eq!(game.blocking_move(Piece::X), None);
+        assert_eq!(game.apply(0, 2).unwrap().winner, Some(Winner::O));
+    }
+
+    #[test]
+    fn blocking_move_stops_the_only_threat() {
+        // o to move faces x's two in the top row
+        let game = Game::from_moves(&[(0, 0), (1, 1), (0, 1)]).unwrap();
+        assert_eq!(game.blocking_move(Piece::O), Some((0, 2)));
+        let game = Game::from_moves(&[(0, 0), (1, 1)]).unwrap();
+        assert_eq!(game.blocking_move(Piece::X), None);
+        assert_eq!(game.blocking_move(Piece::O), None);
+    }
+
+    #[test]
+    fn unicode_grid_has_its_junctions_and_pieces() {
+        let text = Game::from_moves(&[(0, 0), (1, 1)])
+            .unwrap()
+            .to_unicode_string();
+        // 4 inner crossings, 2 junctions on each side and 4 corners
+        assert_eq!(text.matches('┼').count(), 4);
+        for junction in ['┬', '┴', '├', '┤'] {
+            assert_eq!(text.matches(junction).count(), 2);
+        }
+        for corner in ['┌', '┐', '└', '┘'] {
+            assert_eq!(text.matches(corner).count(), 1);
+        }
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[1], "│ X │   │   │");
+        assert_eq!(lines[3], "│   │ O │   │");
+    }
+
+    #[test]
+    fn try_move_reports_each_outcome() {
+        let mut game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        assert_eq!(game.try_move(2, 2).unwrap(), MoveOutcome::Continued);
+        assert_eq!(game.try_move(1, 2).unwrap(), MoveOutcome::Won(Piece::O));
+        assert!(matches!(
+            game.try_move(0, 2),
+            Err(MoveError::GameAlreadyOver)
+        ));
+
+        let mut game = Game::from_compact("XOXXOOOX.").unwrap();
+        assert_eq!(game.try_move(2, 2).unwrap(), MoveOutcome::Tied);
+        assert!(matches!(
+            Game::new().try_move(3, 3),
+            Err(MoveError::InvalidPosition { row: 3, col: 3 })
+        ));
+    }
+
+    #[test]
+    fn apply_leaves_the_original_alone() {
+        let game = Game::from_moves(&[(1, 1)]).unwrap();
+        let before = game.clone();
+        let applied = game.apply(0, 0).unwrap();
+        assert_eq!(game, before);
+        let mut made = game.clone();
+        made.make_move(0, 0).unwrap();
+        assert_eq!(applied, made);
+        assert!(game.apply(1, 1).is_err());
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn custom_renderer_draws_its_own_cells() {
+        // numbers the empty cells so a player can pick one
+        struct NumberedRenderer;
+
+        impl CellRenderer for NumberedRenderer {
+            fn render(&self, cell: Option<Piece>, row: usize, col: usize) -> String {
+                match cell {
+                    None => (row * 3 + col + 1).to_string(),
+                    Some(_) => to_char(&cell).to_string(),
+                }
+            }
+        }
+
+        let game = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        assert_eq!(
+            game.render_with(&NumberedRenderer),
+            "O|2|3\n-----\n4|X|6\n-----\n7|8|9"
+        );
+        assert_eq!(
+            format!("{}\nWinner: None", game.render_with(&PlainRenderer)),
+            game.to_string()
+        );
+    }
+
+    #[test]
+    fn dead_draw_before_the_board_is_full() {
+        let game = Game::from_compact("XOXXOOOX.").unwrap();
+        assert!(!game.is_full());
+        assert!(game.is_dead_draw());
+        // the empty bottom row can still be won
+        assert!(!Game::from_compact("XOXXOO...").unwrap().is_dead_draw());
+        assert!(!Game::new().is_dead_draw());
+    }
+
+    #[test]
+    fn pieces_parse_from_their_letters() {
+        for (c, piece) in [
+            ('X', Piece::X),
+            ('x', Piece::X),
+            ('O', Piece::O),
+            ('o', Piece::O),
+            ('0', Piece::O),
+        ] {
+            assert_eq!(Piece::try_from(c), Ok(piece));
+        }
+        for c in ['Q', '1', ' ', '.', '-'] {
+            assert_eq!(
+                Piece::try_from(c),
+                Err(ParseError::InvalidPiece { found: c })
+            );
+        }
+        assert_eq!(parse_cell(' '), Ok(None));
+        assert_eq!(parse_cell('.'), Ok(None));
+        assert_eq!(parse_cell('x'), Ok(Some(Piece::X)));
+        assert_eq!(
+            parse_cell('?'),
+            Err(ParseError::InvalidPiece { found: '?' })
+        );
+    }
+
+    #[test]
+    fn three_by_three_has_eight_winning_lines() {
+        let lines = winning_lines(3, 3);
+        assert_eq!(lines.len(), 8);
+        assert!(lines.contains(&vec![(0, 2), (1, 1), (2, 0)]));
+        assert_eq!(winning_lines(4, 3).len(), 24);
+        assert_eq!(lines_in(3, 4, 3).len(), 14);
+    }
+
+    #[test]
+    fn index_reads_cells() {
+        let game = Game::from_moves(&[(1, 1), (0, 2)]).unwrap();
+        assert_eq!(game[(1, 1)], Some(Piece::X));
+        assert_eq!(game[(0, 2)], Some(Piece::O));
+        assert_eq!(game[(2, 0)], None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_off_the_board_panics() {
+        let _ = Game::new()[(3, 0)];
+    }
+
+    #[test]
+    fn from_setup_places_pieces_without_alternating() {
+        // a puzzle with o to move that no sequence of alternating moves from x reaches
+        let cells = [((0, 0), Piece::O), ((1, 1), Piece::O), ((2, 0), Piece::X)];
+        let game = Game::from_setup(&cells, Piece::O).unwrap();
+        assert_eq!(game[(0, 0)], Some(Piece::O));
+        assert_eq!(game[(1, 1)], Some(Piece::O));
+        assert_eq!(game[(2, 0)], Some(Piece::X));
+        assert_eq!(game.current_piece(), Piece::O);
+        assert_eq!(game.winner, None);
+        // a setup that already holds a line is won
+        let cells = [((0, 0), Piece::X), ((0, 1), Piece::X), ((0, 2), Piece::X)];
+        let game = Game::from_setup(&cells, Piece::O).unwrap();
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn from_setup_rejects_overlaps_and_off_board_cells() {
+        let cells = [((1, 1), Piece::X), ((1, 1), Piece::O)];
+        assert!(matches!(
+            Game::from_setup(&cells, Piece::X),
+            Err(MoveError::TileNotEmpty {
+                other_piece: Piece::X,
+                row: 1,
+                col: 1
+            })
+        ));
+        assert!(matches!(
+            Game::from_setup(&[((0, 3), Piece::X)], Piece::X),
+            Err(MoveError::InvalidPosition { row: 0, col: 3 })
+        ));
+    }
+
+    #[test]
+    fn four_rotations_return_the_original_board() {
+        let game = Game::from_moves(&[(0, 1), (1, 1), (2, 2)]).unwrap();
+        let turned = game.rotated().rotated().rotated().rotated();
+        assert_eq!(turned.board(), game.board());
+        assert_eq!(turned.current_piece(), game.current_piece());
+        assert_ne!(game.rotated().board(), game.board());
+        assert_eq!(game.rotated()[(1, 2)], Some(Piece::X));
+    }
+
+    #[test]
+    fn transforms_move_the_cells_and_keep_the_turn() {
+        let game = Game::from_moves(&[(0, 1), (1, 1)]).unwrap();
+        assert_eq!(game.flipped_horizontal()[(0, 1)], Some(Piece::X));
+        assert_eq!(game.flipped_vertical()[(2, 1)], Some(Piece::X));
+        assert_eq!(game.transposed()[(1, 0)], Some(Piece::X));
+        for transformed in [
+            game.rotated(),
+            game.flipped_horizontal(),
+            game.flipped_vertical(),
+            game.transposed(),
+        ] {
+            assert_eq!(transformed[(1, 1)], Some(Piece::O));
+            assert_eq!(transformed.current_piece(), Piece::X);
+        }
+        // each mirror undoes itself
+        assert_eq!(game.transposed().transposed().board(), game.board());
+        assert_eq!(
+            game.flipped_vertical().flipped_vertical().board(),
+            game.board()
+        );
+        // a won position is still won after transforming it
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(won.transposed().winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn all_winners_reports_both_lines_of_a_double_win() {
+        let game = Game::from_compact("XXXOOO...").unwrap();
+        assert_eq!(game.all_winners(), vec![Winner::X, Winner::O]);
+        // the first completed line decides the winner
+        assert_eq!(game.winner, Some(Winner::X));
+    }
+
+    #[test]
+    fn heuristic_score_counts_lines_held_alone() {
+        assert_eq!(Game::new().heuristic_score(Piece::X), 0);
+        // x alone holds row 1 and column 1, o alone holds the main diagonal
+        let game = Game::from_moves(&[(0, 1), (0, 0), (1, 0)]).unwrap();
+        assert_eq!(game.heuristic_score(Piece::X), 1);
+        assert_eq!(game.heuristic_score(Piece::O), -1);
+        // the completed row counts as WON_LINE_SCORE, the x on the others as 1 each
+        let won = Game::from_setup(
+            &[((0, 0), Piece::X), ((0, 1), Piece::X), ((0, 2), Piece::X)],
+            Piece::O,
+        )
+        .unwrap();
+        assert_eq!(won.heuristic_score(Piece::X), WON_LINE_SCORE + 5);
+    }
 }
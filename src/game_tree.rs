@@ -1,15 +1,32 @@
+use array2d::Array2D;
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use crate::game::{next_games, Game, Winner};
+use crate::game::{next_games, outcome_for, Board, Game, Outcome, Piece, Symmetry, Winner};
+use crate::search::TieBreak;
 
+#[derive(Clone)]
 pub struct GameTree {
     game: Game,
     edges: Vec<Edge>,
 }
 
+// the alternate form {:#} also shows the move leading to each child
 impl fmt::Display for GameTree {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show_moves = f.alternate();
         write!(
             f,
             "Current State:\n{}\n\n{}",
@@ -17,25 +34,177 @@ impl fmt::Display for GameTree {
             self.edges
                 .iter()
                 .map(|e| {
-                    format!(
-                        "{}\nO wins: {}\nX wins: {}\nTies: {}",
-                        e.child.game, e.x_wins, e.o_wins, e.ties
-                    )
+                    let stats = format!(
+                        "{}\nX wins: {}\nO wins: {}\nTies: {}",
+                        e.node().game,
+                        e.x_wins,
+                        e.o_wins,
+                        e.ties
+                    );
+                    if show_moves {
+                        format!("Move: ({},{})\n{}", e.mv.0, e.mv.1, stats)
+                    } else {
+                        stats
+                    }
                 })
                 .join("\n\n")
         )
     }
 }
 
+#[derive(Clone)]
 struct Edge {
-    child: GameTree,
+    mv: (usize, usize),
+    child: Arc<GameTree>,
+    // takes the cells of child onto the position mv actually reaches, the identity unless
+    // from_symmetric shares child with a rotated or mirrored position
+    symmetry: Symmetry,
+    // child as seen through symmetry, built the first time it is navigated to
+    view: OnceLock<GameTree>,
     x_wins: f32,
     o_wins: f32,
     ties: f32,
+    // child.minimax(), kept so searches over the tree don't recompute it at every level
+    minimax: i32,
+}
+
+impl Edge {
+    fn new(mv: (usize, usize), child: Arc<GameTree>) -> Self {
+        Edge::with_symmetry(mv, child, Symmetry::IDENTITY)
+    }
+
+    fn with_symmetry(mv: (usize, usize), child: Arc<GameTree>, symmetry: Symmetry) -> Self {
+        let (x_wins, o_wins, ties) = child.outcome_distribution();
+        let minimax = child.minimax();
+        Self {
+            mv,
+            child,
+            symmetry,
+            view: OnceLock::new(),
+            x_wins,
+            o_wins,
+            ties,
+            minimax,
+        }
+    }
+
+    // the subtree mv leads to, with its positions and moves as actually reached
+    fn node(&self) -> &GameTree {
+        if self.symmetry == Symmetry::IDENTITY {
+            return &self.child;
+        }
+        self.view
+            .get_or_init(|| self.child.transformed(self.symmetry))
+    }
+}
+
+// game as seen through symmetry, only copied when that changes it
+fn seen_through(game: &Game, symmetry: Symmetry) -> Cow<'_, Game> {
+    if symmetry == Symmetry::IDENTITY {
+        Cow::Borrowed(game)
+    } else {
+        Cow::Owned(game.transformed(symmetry))
+    }
+}
+
+// the boards of a tree being built, split by hash between several sets so that subtrees
+// built on separate threads rarely wait on the same lock
+struct BoardInterner {
+    shards: Vec<Mutex<HashSet<Arc<Board>>>>,
+}
+
+impl BoardInterner {
+    // one set is enough when the tree is built on a single thread
+    const SHARDS: usize = if cfg!(feature = "rayon") { 64 } else { 1 };
+
+    fn new() -> Self {
+        Self {
+            shards: (0..Self::SHARDS)
+                .map(|_| Mutex::new(HashSet::new()))
+                .collect(),
+        }
+    }
+
+    // share the board of game with any identical board interned before
+    fn intern(&self, game: &mut Game) {
+        let mut hasher = DefaultHasher::new();
+        game.board().hash(&mut hasher);
+        let shard = &self.shards[hasher.finish() as usize % self.shards.len()];
+        game.intern_board(&mut shard.lock().unwrap());
+    }
+}
+
+// counts gathered in one pass over a tree, wins and ties count leaves rather than
+// probabilities and a shared subtree counts once for each path to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TreeStats {
+    pub nodes: usize,
+    pub leaves: usize,
+    pub x_wins: usize,
+    pub o_wins: usize,
+    pub ties: usize,
+    pub max_depth: usize,
 }
 
 impl GameTree {
     pub fn from(game: Game) -> Self {
+        GameTree::from_depth(game, usize::MAX)
+    }
+
+    // expand at most max_depth plies, positions at that depth become leaves even if the
+    // game isn't finished and count as no outcome at all in the probability methods
+    pub fn from_depth(game: Game, max_depth: usize) -> Self {
+        GameTree::build(game, max_depth, &BoardInterner::new())
+    }
+
+    // the same position reached by different move orders appears once per path, so every
+    // node's board is interned in boards as it is built to share one copy of it
+    fn build(mut game: Game, max_depth: usize, boards: &BoardInterner) -> Self {
+        boards.intern(&mut game);
+        if game.is_finished() || max_depth == 0 {
+            return Self {
+                game,
+                edges: vec![],
+            };
+        }
+
+        let children: Vec<_> = game
+            .valid_moves()
+            .into_iter()
+            .zip(next_games(&game))
+            .collect();
+
+        // subtrees are independent so they can be built on separate threads
+        #[cfg(feature = "rayon")]
+        let children = children.into_par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let children = children.into_iter();
+
+        let edges = children
+            .map(|(mv, game)| Edge::new(mv, Arc::new(GameTree::build(game, max_depth - 1, boards))))
+            .collect();
+
+        Self { game, edges }
+    }
+
+    // like from, but positions reached by different move orders share one subtree
+    pub fn from_cached(game: Game) -> Self {
+        let mut cache = HashMap::new();
+        GameTree::build_cached(game, &mut cache, Game::clone)
+    }
+
+    // like from_cached, but rotated or mirrored positions also share a subtree, navigating
+    // the tree still gives the positions and moves actually reached
+    pub fn from_symmetric(game: Game) -> Self {
+        let mut cache = HashMap::new();
+        GameTree::build_cached(game, &mut cache, Game::canonical)
+    }
+
+    fn build_cached(
+        game: Game,
+        cache: &mut HashMap<Game, Arc<GameTree>>,
+        key_fn: fn(&Game) -> Game,
+    ) -> Self {
         if game.is_finished() {
             return Self {
                 game,
@@ -43,57 +212,1170 @@ impl GameTree {
             };
         }
 
-        let edges = next_games(&game)
-            .iter()
-            .map(|game| {
-                let child = GameTree::from(game.clone());
-                let x_wins = child.x_wins();
-                let o_wins = child.o_wins();
-                let ties = child.ties();
-                Edge {
-                    child,
-                    x_wins,
-                    o_wins,
-                    ties,
+        let edges = game
+            .valid_moves()
+            .into_iter()
+            .zip(next_games(&game))
+            .map(|(mv, game)| {
+                let key = key_fn(&game);
+                if let Some(child) = cache.get(&key) {
+                    let symmetry = child
+                        .game
+                        .symmetry_to(&game)
+                        .expect("cached position isn't symmetric to the one reached");
+                    return Edge::with_symmetry(mv, child.clone(), symmetry);
                 }
+                let child = Arc::new(GameTree::build_cached(game, cache, key_fn));
+                cache.insert(key, child.clone());
+                Edge::new(mv, child)
             })
             .collect();
 
         Self { game, edges }
     }
 
-    pub fn o_wins(&self) -> f32 {
-        if self.game.is_finished() {
+    // the position at the root of this tree
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // a shallow copy with symmetry applied to the position and the moves below it, the
+    // subtrees further down stay shared
+    fn transformed(&self, symmetry: Symmetry) -> GameTree {
+        let (rows, cols) = (
+            self.game.board().num_rows(),
+            self.game.board().num_columns(),
+        );
+        let game = self.game.transformed(symmetry);
+        let mut edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|e| Edge {
+                mv: symmetry.cell(e.mv, rows, cols),
+                child: e.child.clone(),
+                symmetry: symmetry.after(e.symmetry),
+                view: OnceLock::new(),
+                ..*e
+            })
+            .collect();
+        // keep the children in the order of valid_moves like every other node
+        let order = game.valid_moves();
+        edges.sort_by_key(|e| order.iter().position(|&mv| mv == e.mv));
+        GameTree { game, edges }
+    }
+
+    // the children paired with the move that produced each of them
+    fn moves(&self) -> impl Iterator<Item = ((usize, usize), &Edge)> {
+        self.edges.iter().map(|e| (e.mv, e))
+    }
+
+    // the subtree reached by playing mv, None if mv isn't one of the moves expanded here
+    pub fn child(&self, mv: (usize, usize)) -> Option<&GameTree> {
+        self.edges.iter().find(|e| e.mv == mv).map(Edge::node)
+    }
+
+    // the subtree reached by playing moves in order, None if one of them isn't expanded
+    pub fn subtree_at(&self, moves: &[(usize, usize)]) -> Option<&GameTree> {
+        moves.iter().try_fold(self, |tree, &mv| tree.child(mv))
+    }
+
+    // drop the edges at any depth whose child fails keep, the probabilities then count only
+    // the games left and a position that loses all its children counts as no outcome
+    pub fn retain_edges<F: Fn(&Game) -> bool>(&mut self, keep: F) {
+        self.retain_edges_by(&keep, Symmetry::IDENTITY);
+    }
+
+    // symmetry takes the positions stored below this node onto those actually reached
+    fn retain_edges_by<F: Fn(&Game) -> bool>(&mut self, keep: &F, symmetry: Symmetry) {
+        self.edges
+            .retain(|e| keep(&seen_through(&e.child.game, symmetry.after(e.symmetry))));
+        for edge in &mut self.edges {
+            let child_symmetry = symmetry.after(edge.symmetry);
+            Arc::make_mut(&mut edge.child).retain_edges_by(keep, child_symmetry);
+            *edge = Edge::with_symmetry(edge.mv, edge.child.clone(), edge.symmetry);
+        }
+    }
+
+    // minimax score for the player to move: positive for a win, negative for a loss and
+    // larger in magnitude the sooner the game ends, unexpanded positions score as a tie
+    fn minimax(&self) -> i32 {
+        if self.edges.is_empty() {
+            let remaining = self.game.num_empty() as i32 + 1;
+            return match self
+                .game
+                .winner
+                .map(|w| outcome_for(w, self.game.current_piece()))
+            {
+                Some(Outcome::Win) => remaining,
+                Some(Outcome::Loss) => -remaining,
+                Some(Outcome::Draw) | None => 0,
+            };
+        }
+        self.edges.iter().map(|e| -e.minimax).max().unwrap()
+    }
+
+    // the moves with the best result under optimal play, whatever the number of moves to it
+    fn best_edges(&self) -> Vec<((usize, usize), &Edge)> {
+        let best = self.edges.iter().map(|e| -e.minimax.signum()).max();
+        self.moves()
+            .filter(|(_, e)| Some(-e.minimax.signum()) == best)
+            .collect()
+    }
+
+    // one of best_edges chosen by tie_break, the same way search::best_move chooses
+    fn best_edge_by(&self, tie_break: TieBreak) -> Option<((usize, usize), &Edge)> {
+        let best = self.best_edges();
+        let piece = self.game.current_piece();
+        let chosen = match tie_break {
+            TieBreak::First => best.first(),
+            TieBreak::FastestWin => best.iter().min_by_key(|(_, e)| e.minimax),
+            TieBreak::Random(seed) => best.choose(&mut StdRng::seed_from_u64(seed)),
+            TieBreak::MostThreats => best
+                .iter()
+                .min_by_key(|(_, e)| Reverse(e.child.game.winning_moves(piece).len())),
+        };
+        chosen.copied()
+    }
+
+    // the edge of the fastest win or slowest loss, the move both sides are taken to play
+    // by the methods that assume optimal play
+    fn best_edge(&self) -> Option<((usize, usize), &Edge)> {
+        self.best_edge_by(TieBreak::FastestWin)
+    }
+
+    // an optimal move for the player to move, chosen among equally good moves by tie_break
+    pub fn best_move(&self, tie_break: TieBreak) -> Option<(usize, usize)> {
+        self.best_edge_by(tie_break).map(|(mv, _)| mv)
+    }
+
+    // best_move with TieBreak::FastestWin together with the result it leads to under
+    // optimal play
+    pub fn suggest_move(&self) -> Option<((usize, usize), Winner)> {
+        let (mv, edge) = self.best_edge()?;
+        edge.child.optimal_winner().map(|winner| (mv, winner))
+    }
+
+    // the moves played from here to the end of the game when both sides always play
+    // best_move with TieBreak::FastestWin
+    pub fn principal_variation(&self) -> Vec<(usize, usize)> {
+        let mut moves = Vec::new();
+        let mut tree = self;
+        while let Some((mv, edge)) = tree.best_edge() {
+            moves.push(mv);
+            tree = edge.node();
+        }
+        moves
+    }
+
+    // winner at the end of the game when both sides always play best_edge
+    fn optimal_winner(&self) -> Option<Winner> {
+        match self.best_edge() {
+            Some((_, edge)) => edge.child.optimal_winner(),
+            None => self.game.winner,
+        }
+    }
+
+    // value of the position for the player to move under perfect play over the moves in
+    // this tree: 1 win, 0 tie, -1 loss, unexpanded positions score as a tie
+    pub fn minimax_value(&self) -> i32 {
+        self.alphabeta(-1, 1)
+    }
+
+    fn alphabeta(&self, mut alpha: i32, beta: i32) -> i32 {
+        if self.edges.is_empty() {
+            return match self
+                .game
+                .winner
+                .map(|w| outcome_for(w, self.game.current_piece()))
+            {
+                Some(Outcome::Win) => 1,
+                Some(Outcome::Loss) => -1,
+                Some(Outcome::Draw) | None => 0,
+            };
+        }
+        for edge in &self.edges {
+            alpha = alpha.max(-edge.child.alphabeta(-beta, -alpha));
+            if alpha >= beta {
+                break;
+            }
+        }
+        alpha
+    }
+
+    // call f with every position in the tree and its depth below this node, parents before
+    // their children
+    pub fn visit<F: FnMut(&Game, usize)>(&self, mut f: F) {
+        self.visit_from(0, Symmetry::IDENTITY, &mut f);
+    }
+
+    fn visit_from<F: FnMut(&Game, usize)>(&self, depth: usize, symmetry: Symmetry, f: &mut F) {
+        f(&seen_through(&self.game, symmetry), depth);
+        for edge in &self.edges {
+            edge.child
+                .visit_from(depth + 1, symmetry.after(edge.symmetry), f);
+        }
+    }
+
+    // number of game states in the tree, a shared subtree counts once for each path to it
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .edges
+            .iter()
+            .map(|e| e.child.node_count())
+            .sum::<usize>()
+    }
+
+    // number of game states actually stored, counting a shared subtree only once, which
+    // shows how much from_cached and from_symmetric save over from
+    pub fn distinct_node_count(&self) -> usize {
+        let mut seen = HashSet::new();
+        self.collect_nodes(&mut seen);
+        seen.len()
+    }
+
+    fn collect_nodes(&self, seen: &mut HashSet<*const GameTree>) {
+        if !seen.insert(self as *const GameTree) {
+            return;
+        }
+        for edge in &self.edges {
+            edge.child.collect_nodes(seen);
+        }
+    }
+
+    // number of games in the tree that have no children
+    pub fn leaf_count(&self) -> usize {
+        if self.edges.is_empty() {
+            return 1;
+        }
+        self.edges.iter().map(|e| e.child.leaf_count()).sum()
+    }
+
+    // node_count, leaf_count, depth and leaf outcomes together in a single traversal
+    pub fn statistics(&self) -> TreeStats {
+        if self.edges.is_empty() {
+            let winner = self.game.winner;
+            return TreeStats {
+                nodes: 1,
+                leaves: 1,
+                x_wins: (winner == Some(Winner::X)) as usize,
+                o_wins: (winner == Some(Winner::O)) as usize,
+                ties: (winner == Some(Winner::Tie)) as usize,
+                max_depth: 0,
+            };
+        }
+        self.edges.iter().map(|e| e.child.statistics()).fold(
+            TreeStats {
+                nodes: 1,
+                ..TreeStats::default()
+            },
+            |total, child| TreeStats {
+                nodes: total.nodes + child.nodes,
+                leaves: total.leaves + child.leaves,
+                x_wins: total.x_wins + child.x_wins,
+                o_wins: total.o_wins + child.o_wins,
+                ties: total.ties + child.ties,
+                max_depth: total.max_depth.max(child.max_depth + 1),
+            },
+        )
+    }
+
+    // most moves played from here before the game ends
+    pub fn depth(&self) -> usize {
+        self.edges
+            .iter()
+            .map(|e| e.child.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // fewest moves, counting both players, after which piece can have won
+    pub fn shortest_win(&self, piece: Piece) -> Option<usize> {
+        if self.edges.is_empty() {
             return match self.game.winner {
-                Some(Winner::O) => 1.0,
-                _ => 0.0,
+                Some(winner) if winner == Winner::from(piece) => Some(0),
+                _ => None,
             };
         }
-        assert_ne!(self.edges.len(), 0);
-        self.edges.iter().map(|e| e.child.o_wins()).sum::<f32>() / self.edges.len() as f32
+        self.edges
+            .iter()
+            .filter_map(|e| e.child.shortest_win(piece))
+            .map(|moves| moves + 1)
+            .min()
     }
 
-    // percentage of the times that x wins
-    pub fn x_wins(&self) -> f32 {
-        if self.game.is_finished() {
+    // moves from here to some leaf won by target, depth first in move order
+    pub fn find_path_to(&self, target: Winner) -> Option<Vec<(usize, usize)>> {
+        if self.edges.is_empty() {
+            return (self.game.winner == Some(target)).then(Vec::new);
+        }
+        self.moves().find_map(|(mv, edge)| {
+            let mut path = edge.node().find_path_to(target)?;
+            path.insert(0, mv);
+            Some(path)
+        })
+    }
+
+    // graphviz digraph of the tree down to max_depth plies, edges labeled with their move
+    pub fn to_dot(&self, max_depth: usize) -> String {
+        let mut lines = vec![
+            String::from("digraph game_tree {"),
+            String::from("    node [shape=box, fontname=\"monospace\"];"),
+        ];
+        self.write_dot(max_depth, &mut 0, &mut lines);
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+
+    // adds this node and its children to lines, returning the id given to this node
+    fn write_dot(&self, max_depth: usize, next_id: &mut usize, lines: &mut Vec<String>) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        lines.push(format!(
+            "    n{} [label=\"{}\"];",
+            id,
+            self.game.to_string().replace('\n', "\\n")
+        ));
+        if max_depth > 0 {
+            for ((row, col), edge) in self.moves() {
+                let child_id = edge.node().write_dot(max_depth - 1, next_id, lines);
+                lines.push(format!(
+                    "    n{} -> n{} [label=\"({},{})\"];",
+                    id, child_id, row, col
+                ));
+            }
+        }
+        id
+    }
+
+    // json object for each node down to max_depth plies, holding its board and winner,
+    // the move that led to it and its probabilities under uniform random play
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self, max_depth: usize) -> String {
+        self.json_node(None, max_depth).to_string()
+    }
+
+    #[cfg(feature = "serde")]
+    fn json_node(&self, mv: Option<(usize, usize)>, max_depth: usize) -> serde_json::Value {
+        let (x_wins, o_wins, ties) = self.outcome_distribution();
+        let children: Vec<_> = match max_depth {
+            0 => Vec::new(),
+            _ => self
+                .moves()
+                .map(|(mv, edge)| edge.node().json_node(Some(mv), max_depth - 1))
+                .collect(),
+        };
+        serde_json::json!({
+            "move": mv,
+            "board": self.game.board().as_rows(),
+            "winner": self.game.winner,
+            "x_wins": x_wins,
+            "o_wins": o_wins,
+            "ties": ties,
+            "children": children,
+        })
+    }
+
+    // probabilities of (x wins, o wins, tie) under uniform random play, found in one pass
+    // over the children
+    pub fn outcome_distribution(&self) -> (f32, f32, f32) {
+        if self.edges.is_empty() {
             return match self.game.winner {
-                Some(Winner::X) => 1.0,
-                _ => 0.0,
+                Some(Winner::X) => (1.0, 0.0, 0.0),
+                Some(Winner::O) => (0.0, 1.0, 0.0),
+                Some(Winner::Tie) => (0.0, 0.0, 1.0),
+                None => (0.0, 0.0, 0.0),
             };
         }
-        assert_ne!(self.edges.len(), 0);
-        self.edges.iter().map(|e| e.child.x_wins()).sum::<f32>() / self.edges.len() as f32
+        let n = self.edges.len() as f32;
+        let (x_wins, o_wins, ties) = self.edges.iter().fold((0.0, 0.0, 0.0), |sums, e| {
+            (sums.0 + e.x_wins, sums.1 + e.o_wins, sums.2 + e.ties)
+        });
+        (x_wins / n, o_wins / n, ties / n)
+    }
+
+    // expected value of eval over the leaves reached by uniform random play, eval scores
+    // each leaf including those left unexpanded by from_depth
+    pub fn evaluate<F: Fn(&Game) -> f32>(&self, eval: F) -> f32 {
+        self.evaluate_by(&eval, Symmetry::IDENTITY)
+    }
+
+    fn evaluate_by<F: Fn(&Game) -> f32>(&self, eval: &F, symmetry: Symmetry) -> f32 {
+        if self.edges.is_empty() {
+            return eval(&seen_through(&self.game, symmetry));
+        }
+        self.edges
+            .iter()
+            .map(|e| e.child.evaluate_by(eval, symmetry.after(e.symmetry)))
+            .sum::<f32>()
+            / self.edges.len() as f32
+    }
+
+    pub fn o_wins(&self) -> f32 {
+        self.outcome_distribution().1
+    }
+
+    // percentage of the times that x wins
+    pub fn x_wins(&self) -> f32 {
+        self.outcome_distribution().0
     }
 
     // percentage of the times that game ends in tie
     pub fn ties(&self) -> f32 {
-        if self.game.is_finished() {
+        self.outcome_distribution().2
+    }
+
+    // x_wins minus o_wins, positive when random play favors X and negative when it
+    // favors O
+    pub fn advantage(&self) -> f32 {
+        let (x_wins, o_wins, _) = self.outcome_distribution();
+        x_wins - o_wins
+    }
+
+    // the result with the highest probability under uniform random play, a tie wins any
+    // draw between the probabilities
+    pub fn most_likely_outcome(&self) -> Winner {
+        let (x_wins, o_wins, ties) = self.outcome_distribution();
+        if ties >= x_wins && ties >= o_wins {
+            Winner::Tie
+        } else if x_wins >= o_wins {
+            Winner::X
+        } else {
+            Winner::O
+        }
+    }
+
+    // average number of moves on the board, counting those already played, when the game
+    // ends under uniform random play
+    pub fn expected_game_length(&self) -> f32 {
+        if self.edges.is_empty() {
+            return self.game.move_count() as f32;
+        }
+        self.edges
+            .iter()
+            .map(|e| e.child.expected_game_length())
+            .sum::<f32>()
+            / self.edges.len() as f32
+    }
+
+    // probability that skilled wins when it always plays best_move with
+    // TieBreak::FastestWin and its opponent moves uniformly at random
+    pub fn win_prob_vs_random(&self, skilled: Piece) -> f32 {
+        if self.edges.is_empty() {
             return match self.game.winner {
-                Some(Winner::Tie) => 1.0,
+                Some(winner) if winner == Winner::from(skilled) => 1.0,
                 _ => 0.0,
             };
         }
-        assert_ne!(self.edges.len(), 0);
-        self.edges.iter().map(|e| e.child.ties()).sum::<f32>() / self.edges.len() as f32
+        if self.game.current_piece() == skilled {
+            let (_, edge) = self.best_edge().unwrap();
+            return edge.child.win_prob_vs_random(skilled);
+        }
+        self.edges
+            .iter()
+            .map(|e| e.child.win_prob_vs_random(skilled))
+            .sum::<f32>()
+            / self.edges.len() as f32
+    }
+
+    // probability that o wins when both players always play best_move with
+    // TieBreak::FastestWin
+    pub fn o_wins_optimal(&self) -> f32 {
+        match self.optimal_winner() {
+            Some(Winner::O) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    // probability that x wins when both players always play best_move with
+    // TieBreak::FastestWin
+    pub fn x_wins_optimal(&self) -> f32 {
+        match self.optimal_winner() {
+            Some(Winner::X) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    // probability of a tie when both players always play best_move with
+    // TieBreak::FastestWin
+    pub fn ties_optimal(&self) -> f32 {
+        match self.optimal_winner() {
+            Some(Winner::Tie) => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+// the subtrees directly below a node, in the order of valid_moves
+pub struct Children<'a> {
+    edges: std::slice::Iter<'a, Edge>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = &'a GameTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.edges.next().map(Edge::node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Children<'_> {}
+
+impl<'a> IntoIterator for &'a GameTree {
+    type Item = &'a GameTree;
+    type IntoIter = Children<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Children {
+            edges: self.edges.iter(),
+        }
+    }
+}
+
+// probability under uniform random play that piece wins after x opens in each cell of a
+// new game
+pub fn opening_heatmap(piece: Piece) -> Array2D<f32> {
+    let game = Game::new();
+    let mut heatmap =
+        Array2D::filled_with(0.0, game.board().num_rows(), game.board().num_columns());
+    for (mv, edge) in GameTree::from_cached(game).moves() {
+        heatmap[mv] = match piece {
+            Piece::X => edge.x_wins,
+            Piece::O => edge.o_wins,
+        };
+    }
+    heatmap
+}
+
+// a game tree whose children are only built the first time they're asked for, so boards
+// too large for GameTree::from can still be explored a few moves at a time
+pub struct LazyGameTree {
+    game: Game,
+    children: OnceCell<Vec<((usize, usize), LazyGameTree)>>,
+}
+
+impl LazyGameTree {
+    pub fn new(game: Game) -> Self {
+        Self {
+            game,
+            children: OnceCell::new(),
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn is_expanded(&self) -> bool {
+        self.children.get().is_some()
+    }
+
+    // build the children if they haven't been built yet
+    pub fn expand(&self) {
+        self.children();
+    }
+
+    // each legal move with the subtree it leads to, building them on first use
+    pub fn children(&self) -> &[((usize, usize), LazyGameTree)] {
+        self.children.get_or_init(|| {
+            self.game
+                .valid_moves()
+                .into_iter()
+                .zip(next_games(&self.game))
+                .map(|(mv, game)| (mv, LazyGameTree::new(game)))
+                .collect()
+        })
+    }
+
+    pub fn child(&self, mv: (usize, usize)) -> Option<&LazyGameTree> {
+        self.children()
+            .iter()
+            .find(|(child_mv, _)| *child_mv == mv)
+            .map(|(_, child)| child)
+    }
+
+    pub fn subtree_at(&self, moves: &[(usize, usize)]) -> Option<&LazyGameTree> {
+        moves.iter().try_fold(self, |tree, &mv| tree.child(mv))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn cached_tree_stores_each_position_once() {
+        let cached = GameTree::from_cached(Game::new());
+        assert_eq!(cached.node_count(), 549946);
+        assert_eq!(cached.distinct_node_count(), 5478);
+
+        let game = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        let naive = GameTree::from(game.clone());
+        let cached = GameTree::from_cached(game);
+        assert_eq!(naive.distinct_node_count(), naive.node_count());
+        assert!(cached.distinct_node_count() < naive.node_count() / 4);
+        assert_close(cached.x_wins(), naive.x_wins());
+        assert_close(cached.o_wins(), naive.o_wins());
+        assert_close(cached.ties(), naive.ties());
+    }
+
+    #[test]
+    fn best_move_answers_a_corner_with_the_center() {
+        for corner in [(0, 0), (0, 2), (2, 0), (2, 2)] {
+            let game = Game::from_moves(&[corner]).unwrap();
+            assert_eq!(
+                GameTree::from(game).best_move(TieBreak::FastestWin),
+                Some((1, 1))
+            );
+        }
+    }
+
+    #[test]
+    fn best_move_blocks_and_prefers_winning_now() {
+        // x threatens the top row, o has to block
+        let game = Game::from_moves(&[(0, 0), (1, 1), (0, 1)]).unwrap();
+        assert_eq!(
+            GameTree::from(game).best_move(TieBreak::FastestWin),
+            Some((0, 2))
+        );
+        // x wins at once rather than blocking o at (1, 2)
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (2, 0), (2, 1)]).unwrap();
+        assert_eq!(
+            GameTree::from(game).best_move(TieBreak::FastestWin),
+            Some((0, 2))
+        );
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(GameTree::from(game).best_move(TieBreak::FastestWin), None);
+    }
+
+    #[test]
+    fn minimax_value_of_opening_is_a_tie() {
+        assert_eq!(GameTree::from_cached(Game::new()).minimax_value(), 0);
+    }
+
+    #[test]
+    fn minimax_value_only_searches_the_tree() {
+        // x to move can win at (0, 2)
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        let mut tree = GameTree::from(game.clone());
+        assert_eq!(tree.minimax_value(), 1);
+        assert_eq!(tree.best_move(TieBreak::FastestWin), Some((0, 2)));
+
+        // with (0, 2) never played x can only block o and hold the draw
+        tree.retain_edges(|child| child.piece_at(0, 2).is_none());
+        assert_eq!(tree.minimax_value(), 0);
+        assert_eq!(tree.best_move(TieBreak::FastestWin), Some((1, 2)));
+
+        // the frontier of a depth limited tree scores as a tie rather than being searched
+        assert_eq!(GameTree::from_depth(game, 0).minimax_value(), 0);
+        assert_eq!(
+            GameTree::from_depth(Game::with_size(4), 1).minimax_value(),
+            0
+        );
+    }
+
+    #[test]
+    fn optimal_play_from_the_opening_always_ties() {
+        let tree = GameTree::from_cached(Game::new());
+        assert_eq!(tree.x_wins_optimal(), 0.0);
+        assert_eq!(tree.o_wins_optimal(), 0.0);
+        assert_eq!(tree.ties_optimal(), 1.0);
+        // unlike uniform play, which x wins more often than not
+        assert!(tree.x_wins() > 0.5);
+
+        // x to move can win at (0, 2)
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        let tree = GameTree::from(game);
+        assert_eq!(tree.x_wins_optimal(), 1.0);
+        assert!(tree.x_wins() < 1.0);
+    }
+
+    #[test]
+    fn dot_has_one_node_per_game_to_the_depth() {
+        let dot = GameTree::from_depth(Game::new(), 2).to_dot(2);
+        assert!(dot.starts_with("digraph game_tree {"));
+        assert!(dot.ends_with('}'));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        // 1 + 9 + 72 nodes, each but the root reached by an edge
+        let nodes = dot
+            .lines()
+            .filter(|line| line.contains("[label=\"") && !line.contains("->"));
+        assert_eq!(nodes.count(), 82);
+        assert_eq!(dot.matches(" -> ").count(), 81);
+        // deeper children are left out
+        let dot = GameTree::from_depth(Game::new(), 3).to_dot(1);
+        assert_eq!(dot.matches(" -> ").count(), 9);
+    }
+
+    #[test]
+    fn depth_limited_4x4_tree_has_every_position() {
+        // built on separate threads with the rayon feature, the tree is the same
+        let tree = GameTree::from_depth(Game::with_size(4), 3);
+        assert_eq!(tree.node_count(), 1 + 16 + 16 * 15 + 16 * 15 * 14);
+        assert_eq!(tree.leaf_count(), 16 * 15 * 14);
+        let moves: Vec<_> = tree.moves().map(|(mv, _)| mv).collect();
+        assert_eq!(moves, tree.game().valid_moves());
+    }
+
+    #[test]
+    fn full_tree_node_and_leaf_counts() {
+        let tree = GameTree::from(Game::new());
+        assert_eq!(tree.node_count(), 549946);
+        assert_eq!(tree.leaf_count(), 255168);
+        // after the center and a corner, 7 cells are left but some games end early
+        let tree = GameTree::from(Game::from_moves(&[(1, 1), (0, 0)]).unwrap());
+        assert_eq!(tree.leaf_count(), 3198);
+        let finished = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        let tree = GameTree::from(finished);
+        assert_eq!((tree.node_count(), tree.leaf_count()), (1, 1));
+    }
+
+    #[test]
+    fn depth_and_shortest_wins() {
+        let tree = GameTree::from_cached(Game::new());
+        assert_eq!(tree.depth(), 9);
+        assert_eq!(tree.shortest_win(Piece::X), Some(5));
+        assert_eq!(tree.shortest_win(Piece::O), Some(6));
+
+        // the last cell ties the game, so neither piece can win
+        let tree = GameTree::from(Game::from_compact("XOXXOOOX.").unwrap());
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.shortest_win(Piece::X), None);
+        assert_eq!(tree.shortest_win(Piece::O), None);
+    }
+
+    #[test]
+    fn outcome_distribution_matches_the_single_outcomes() {
+        let tree = GameTree::from_cached(Game::new());
+        let (x_wins, o_wins, ties) = tree.outcome_distribution();
+        assert_close(x_wins, tree.x_wins());
+        assert_close(o_wins, tree.o_wins());
+        assert_close(ties, tree.ties());
+        assert_close(x_wins + o_wins + ties, 1.0);
+    }
+
+    #[test]
+    fn suggest_move_finds_the_forced_win() {
+        // x to move wins at once at (0, 2)
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        assert_eq!(
+            GameTree::from(game).suggest_move(),
+            Some(((0, 2), Winner::X))
+        );
+        // o to move can only delay the fork x has set up
+        let game = Game::from_moves(&[(1, 1), (0, 1), (0, 0)]).unwrap();
+        let (mv, winner) = GameTree::from(game).suggest_move().unwrap();
+        assert_eq!((mv, winner), ((2, 2), Winner::X));
+        assert_eq!(
+            GameTree::from_cached(Game::new()).suggest_move().unwrap().1,
+            Winner::Tie
+        );
+    }
+
+    #[test]
+    fn skilled_player_wins_more_than_uniform_play() {
+        let tree = GameTree::from_cached(Game::new());
+        let skilled_x = tree.win_prob_vs_random(Piece::X);
+        assert!(skilled_x > tree.x_wins());
+        assert!(tree.win_prob_vs_random(Piece::O) > tree.o_wins());
+        assert!(skilled_x <= 1.0);
+    }
+
+    #[test]
+    fn depth_zero_tree_is_a_single_leaf() {
+        let tree = GameTree::from_depth(Game::new(), 0);
+        assert!(tree.moves().next().is_none());
+        assert_eq!((tree.node_count(), tree.leaf_count()), (1, 1));
+        // an unexpanded position has no outcome at all
+        assert_eq!(tree.outcome_distribution(), (0.0, 0.0, 0.0));
+        assert_eq!(GameTree::from_depth(Game::new(), 1).leaf_count(), 9);
+        assert_eq!(GameTree::from_depth(Game::new(), 2).depth(), 2);
+    }
+
+    #[test]
+    fn x_is_the_most_likely_winner_from_the_opening() {
+        assert_eq!(
+            GameTree::from_cached(Game::new()).most_likely_outcome(),
+            Winner::X
+        );
+        // x can force a win here, and wins most random games from it too
+        let game = Game::from_moves(&[(1, 1), (0, 1), (0, 0)]).unwrap();
+        assert_eq!(GameTree::from(game).most_likely_outcome(), Winner::X);
+        let tie = Game::from_compact("XOXXOOOXX").unwrap();
+        assert_eq!(GameTree::from(tie).most_likely_outcome(), Winner::Tie);
+    }
+
+    #[test]
+    fn evaluate_with_a_win_indicator_is_x_wins() {
+        let tree = GameTree::from_cached(Game::new());
+        let x_won = |game: &Game| (game.winner == Some(Winner::X)) as u8 as f32;
+        assert_close(tree.evaluate(x_won), tree.x_wins());
+        assert_close(tree.evaluate(|_| 1.0), 1.0);
+        // unexpanded leaves are scored by eval as well
+        let shallow = GameTree::from_depth(Game::new(), 1);
+        assert_close(shallow.evaluate(|game| game.move_count() as f32), 1.0);
+    }
+
+    #[test]
+    fn path_to_a_win_replays_to_that_win() {
+        let tree = GameTree::from_cached(Game::new());
+        for target in [Winner::X, Winner::O, Winner::Tie] {
+            let path = tree.find_path_to(target).unwrap();
+            assert_eq!(Game::from_moves(&path).unwrap().winner, Some(target));
+        }
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!(
+            GameTree::from(won.clone()).find_path_to(Winner::X),
+            Some(vec![])
+        );
+        assert_eq!(GameTree::from(won).find_path_to(Winner::O), None);
+    }
+
+    #[test]
+    fn symmetric_tree_navigates_real_positions() {
+        let root = Game::new();
+        let tree = GameTree::from_symmetric(root.clone());
+        for mv in root.valid_moves() {
+            let child = tree.child(mv).unwrap();
+            assert_eq!(
+                child.game().board(),
+                root.apply(mv.0, mv.1).unwrap().board()
+            );
+        }
+        let node = tree.subtree_at(&[(2, 2), (0, 0)]).unwrap();
+        assert_eq!(
+            node.game().board(),
+            Game::from_moves(&[(2, 2), (0, 0)]).unwrap().board()
+        );
+        for (child, mv) in node.into_iter().zip(node.game().valid_moves()) {
+            assert_eq!(child.game(), &node.game().apply(mv.0, mv.1).unwrap());
+        }
+    }
+
+    #[test]
+    fn symmetric_tree_paths_replay() {
+        for game in [
+            Game::new(),
+            Game::from_moves(&[(2, 2)]).unwrap(),
+            Game::from_moves(&[(0, 1), (2, 0)]).unwrap(),
+        ] {
+            let tree = GameTree::from_symmetric(game.clone());
+            let replay = |moves: &[(usize, usize)]| {
+                moves.iter().fold(game.clone(), |game, &(row, col)| {
+                    game.apply(row, col).unwrap()
+                })
+            };
+
+            let line = replay(&tree.principal_variation());
+            assert!(line.is_finished());
+            let value = match outcome_for(line.winner.unwrap(), game.current_piece()) {
+                Outcome::Win => 1,
+                Outcome::Draw => 0,
+                Outcome::Loss => -1,
+            };
+            assert_eq!(value, crate::search::alphabeta(&game, -1, 1));
+            for target in [Winner::X, Winner::O, Winner::Tie] {
+                let path = tree.find_path_to(target).unwrap();
+                assert_eq!(replay(&path).winner, Some(target));
+            }
+
+            let mut boards = Vec::new();
+            tree.visit(|game, depth| {
+                if depth == 1 {
+                    boards.push(game.board().clone());
+                }
+            });
+            let expected: Vec<_> = next_games(&game)
+                .iter()
+                .map(|g| g.board().clone())
+                .collect();
+            assert_eq!(boards, expected);
+        }
+    }
+
+    #[test]
+    fn child_is_the_game_after_the_move() {
+        let root = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        let tree = GameTree::from_cached(root.clone());
+        for (row, col) in root.valid_moves() {
+            assert_eq!(
+                tree.child((row, col)).unwrap().game(),
+                &root.apply(row, col).unwrap()
+            );
+        }
+        assert!(tree.child((1, 1)).is_none());
+        assert!(tree.child((3, 3)).is_none());
+    }
+
+    #[test]
+    fn edge_moves_reproduce_children() {
+        let game = Game::from_moves(&[(1, 1)]).unwrap();
+        let tree = GameTree::from(game.clone());
+        for edge in &tree.edges {
+            assert_eq!(edge.child.game, game.apply(edge.mv.0, edge.mv.1).unwrap());
+        }
+    }
+
+    #[test]
+    fn display_labels_each_child() {
+        let game = Game::from_moves(&[(0, 0), (1, 1), (0, 1), (0, 2), (2, 0)]).unwrap();
+        let tree = GameTree::from(game);
+        let edge = &tree.edges[0];
+        let plain = tree.to_string();
+        assert!(plain.contains(&format!(
+            "X wins: {}\nO wins: {}\nTies: {}",
+            edge.x_wins, edge.o_wins, edge.ties
+        )));
+        assert!(!plain.contains("Move:"));
+        let alternate = format!("{:#}", tree);
+        for (row, col) in tree.game.valid_moves() {
+            assert!(alternate.contains(&format!("Move: ({},{})", row, col)));
+        }
+    }
+
+    #[test]
+    fn statistics_of_the_full_tree() {
+        assert_eq!(
+            GameTree::from_cached(Game::new()).statistics(),
+            TreeStats {
+                nodes: 549946,
+                leaves: 255168,
+                x_wins: 131184,
+                o_wins: 77904,
+                ties: 46080,
+                max_depth: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn o_first_mirrors_x_first() {
+        let x_first = GameTree::from_cached(Game::new());
+        let o_first = GameTree::from_cached(Game::with_starting_piece(Piece::O));
+        assert_close(o_first.x_wins(), x_first.o_wins());
+        assert_close(o_first.o_wins(), x_first.x_wins());
+        assert_close(o_first.ties(), x_first.ties());
+    }
+
+    #[test]
+    fn subtree_at_follows_the_moves() {
+        let tree = GameTree::from_cached(Game::new());
+        let subtree = tree.subtree_at(&[(1, 1), (0, 2)]).unwrap();
+        assert_eq!(subtree.game().move_count(), 2);
+        assert_eq!(
+            subtree.game(),
+            &Game::from_moves(&[(1, 1), (0, 2)]).unwrap()
+        );
+        assert_eq!(tree.subtree_at(&[]).unwrap().game(), &Game::new());
+        assert!(tree.subtree_at(&[(1, 1), (1, 1)]).is_none());
+        // a clone of the subtree stands on its own
+        let owned = subtree.clone();
+        assert_eq!(owned.node_count(), subtree.node_count());
+    }
+
+    #[test]
+    fn visit_calls_back_once_per_node() {
+        let tree = GameTree::from_cached(Game::from_moves(&[(1, 1)]).unwrap());
+        let mut calls = 0;
+        let mut roots = Vec::new();
+        tree.visit(|game, depth| {
+            calls += 1;
+            if depth == 0 {
+                roots.push(game.clone());
+            }
+        });
+        assert_eq!(calls, tree.node_count());
+        assert_eq!(roots, vec![tree.game().clone()]);
+    }
+
+    #[test]
+    fn symmetric_tree_has_the_same_outcomes_in_fewer_positions() {
+        let naive = GameTree::from(Game::new());
+        let symmetric = GameTree::from_symmetric(Game::new());
+        assert_close(symmetric.x_wins(), naive.x_wins());
+        assert_close(symmetric.o_wins(), naive.o_wins());
+        assert_close(symmetric.ties(), naive.ties());
+        // the 765 positions that differ up to rotation and reflection
+        assert_eq!(symmetric.distinct_node_count(), 765);
+        assert_eq!(naive.distinct_node_count(), 549946);
+    }
+
+    #[test]
+    fn center_opening_wins_most_often() {
+        let heatmap = opening_heatmap(Piece::X);
+        let center = heatmap[(1, 1)];
+        for (row, col) in Game::new().valid_moves() {
+            if (row, col) != (1, 1) {
+                assert!(heatmap[(row, col)] < center);
+            }
+        }
+        // the four corners are the same opening turned
+        assert_close(heatmap[(0, 0)], heatmap[(2, 2)]);
+        let o_heatmap = opening_heatmap(Piece::O);
+        assert!(o_heatmap[(1, 1)] < o_heatmap[(0, 1)]);
+    }
+
+    #[test]
+    fn expected_game_length_counts_the_remaining_plies() {
+        // x can win at once in (0, 2), otherwise the game goes on
+        let tree = GameTree::from(Game::from_compact("XX.OO....").unwrap());
+        assert_eq!(tree.child((0, 2)).unwrap().expected_game_length(), 5.0);
+        let length = tree.expected_game_length();
+        assert!(length > 5.0 && length < 9.0, "{}", length);
+        // whatever is played into the last two cells, the board fills up
+        let tree = GameTree::from(Game::from_compact("XOX.OXOX.").unwrap());
+        assert_eq!(tree.expected_game_length(), 9.0);
+        let length = GameTree::from_cached(Game::new()).expected_game_length();
+        assert!(length > 5.0 && length < 9.0, "{}", length);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_export_reloads_to_the_same_probabilities() {
+        let tree = GameTree::from_cached(Game::from_moves(&[(1, 1)]).unwrap());
+        let json: serde_json::Value = serde_json::from_str(&tree.to_json(1)).unwrap();
+        let (x_wins, o_wins, ties) = tree.outcome_distribution();
+        assert_close(json["x_wins"].as_f64().unwrap() as f32, x_wins);
+        assert_close(json["o_wins"].as_f64().unwrap() as f32, o_wins);
+        assert_close(json["ties"].as_f64().unwrap() as f32, ties);
+        assert!(json["move"].is_null());
+        let children = json["children"].as_array().unwrap();
+        assert_eq!(children.len(), 8);
+        assert_eq!(children[0]["move"], serde_json::json!([0, 0]));
+        assert!(children[0]["children"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn dropping_losing_moves_raises_the_win_probability() {
+        let game = Game::from_moves(&[(0, 0), (1, 1)]).unwrap();
+        let mut tree = GameTree::from(game);
+        let before = tree.x_wins();
+        let o_before = tree.o_wins();
+        // keep only the x moves after which o can't force a win
+        tree.retain_edges(|game| {
+            game.current_piece() != Piece::O || crate::search::alphabeta(game, -1, 1) < 1
+        });
+        assert!(tree.x_wins() > before);
+        assert!(tree.o_wins() < o_before);
+    }
+
+    #[test]
+    fn principal_variation_reaches_the_minimax_result() {
+        for game in [
+            Game::new(),
+            Game::from_moves(&[(1, 1), (0, 1), (0, 0)]).unwrap(),
+            Game::from_compact("XX.OO....").unwrap(),
+        ] {
+            let tree = GameTree::from_cached(game.clone());
+            let mut end = game.clone();
+            for (row, col) in tree.principal_variation() {
+                end.make_move(row, col).unwrap();
+            }
+            assert!(end.is_finished());
+            let value = match outcome_for(end.winner.unwrap(), game.current_piece()) {
+                Outcome::Win => 1,
+                Outcome::Draw => 0,
+                Outcome::Loss => -1,
+            };
+            assert_eq!(value, tree.minimax_value());
+        }
+    }
+
+    #[test]
+    fn lazy_tree_reaches_the_same_nodes_as_the_eager_one() {
+        let eager = GameTree::from_cached(Game::new());
+        let lazy = LazyGameTree::new(Game::new());
+        assert!(!lazy.is_expanded());
+        let path = [(1, 1), (0, 0), (2, 2), (0, 2)];
+        let node = lazy.subtree_at(&path).unwrap();
+        assert_eq!(node.game(), eager.subtree_at(&path).unwrap().game());
+        assert!(lazy.is_expanded());
+        // only the nodes along the path have been expanded
+        assert!(!lazy.child((0, 1)).unwrap().is_expanded());
+        assert!(!node.is_expanded());
+        let moves: Vec<_> = node.children().iter().map(|(mv, _)| *mv).collect();
+        let eager_moves: Vec<_> = eager
+            .subtree_at(&path)
+            .unwrap()
+            .moves()
+            .map(|(mv, _)| mv)
+            .collect();
+        assert_eq!(moves, eager_moves);
+        assert!(lazy.subtree_at(&[(1, 1), (1, 1)]).is_none());
+    }
+
+    #[test]
+    fn advantage_is_x_wins_minus_o_wins() {
+        assert!(GameTree::from(Game::new()).advantage() > 0.0);
+        // X to move with O threatening both the center and the bottom right corner, taking
+        // either of those leaves X winning half the time and the bottom middle loses, X wins
+        // a third of the games and O two thirds
+        let tree = GameTree::from(Game::from_compact("XXOX.OO..").unwrap());
+        assert!((tree.x_wins() - 1.0 / 3.0).abs() < 1e-6);
+        assert!((tree.o_wins() - 2.0 / 3.0).abs() < 1e-6);
+        assert!((tree.advantage() + 1.0 / 3.0).abs() < 1e-6);
+        assert!(tree.advantage() < 0.0);
+    }
+
+    #[test]
+    fn nodes_with_the_same_board_share_it() {
+        let tree = GameTree::from_depth(Game::new(), 4);
+        let mut nodes = 0;
+        let mut boards = HashSet::new();
+        let mut allocations = HashSet::new();
+        tree.visit(|game, _| {
+            nodes += 1;
+            boards.insert(game.board().clone());
+            allocations.insert(game.board() as *const Board);
+        });
+        // 1 + 9 + 72 + 504 + 3024 nodes hold 1 + 9 + 72 + 252 + 756 boards
+        assert_eq!(nodes, 3610);
+        assert_eq!(boards.len(), 1090);
+        assert_eq!(allocations.len(), 1090);
+    }
+
+    #[test]
+    fn iterating_a_tree_yields_its_children() {
+        let game = Game::from_moves(&[(1, 1), (0, 0)]).unwrap();
+        let tree = GameTree::from_depth(game.clone(), 1);
+        assert_eq!((&tree).into_iter().count(), game.valid_moves().len());
+        let mut games = HashSet::new();
+        for child in &tree {
+            games.insert(child.game().to_compact());
+        }
+        let expected: HashSet<String> = next_games(&game).iter().map(Game::to_compact).collect();
+        assert_eq!(games, expected);
+        // a finished game has no children
+        let won = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]).unwrap();
+        assert_eq!((&GameTree::from(won)).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn best_move_agrees_with_the_search_for_every_tie_break() {
+        for compact in ["X........", "X...O...X", ".....XOOX", "XX.OO...."] {
+            let game = Game::from_compact(compact).unwrap();
+            let tree = GameTree::from_cached(game.clone());
+            for tie_break in [
+                TieBreak::First,
+                TieBreak::FastestWin,
+                TieBreak::Random(3),
+                TieBreak::MostThreats,
+            ] {
+                assert_eq!(
+                    tree.best_move(tie_break),
+                    crate::search::best_move(&game, None, tie_break),
+                    "{} {:?}",
+                    compact,
+                    tie_break
+                );
+            }
+        }
     }
 }
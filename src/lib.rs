@@ -1,2 +1,7 @@
+pub mod bitboard;
 pub mod game;
 pub mod game_tree;
+pub mod player;
+pub mod search;
+#[cfg(feature = "wasm")]
+pub mod wasm;
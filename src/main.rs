@@ -1,6 +1,31 @@
-use tictactoe::{game::Game, game_tree::GameTree};
+use std::env;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
 
-fn main() {
+use tictactoe::{
+    game::{move_to_notation, parse_move, Game, Piece, Winner},
+    game_tree::GameTree,
+    player::{play_series, HeuristicPlayer, MinimaxPlayer, Player, RandomPlayer},
+};
+
+#[cfg(feature = "serde")]
+const USAGE: &str = "usage: tictactoe [stats | play | vs --difficulty easy|hard | save <file> | \
+                     load <file> | tournament --players <name,...> [--games <n>]]";
+#[cfg(not(feature = "serde"))]
+const USAGE: &str = "usage: tictactoe [stats | play | vs --difficulty easy|hard | \
+                     tournament --players <name,...> [--games <n>]]";
+
+// names accepted by tournament --players
+const PLAYERS: [&str; 3] = ["random", "heuristic", "minimax"];
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(1);
+}
+
+fn print_stats() {
     let game_tree = GameTree::from(Game::new());
     println!("{}", game_tree);
     println!(
@@ -11,3 +36,235 @@ fn main() {
         game_tree.x_wins() + game_tree.o_wins() + game_tree.ties()
     );
 }
+
+// a move typed as "row col"
+fn parse_coords(line: &str) -> Option<(usize, usize)> {
+    let mut parts = line.split_whitespace().map(str::parse::<usize>);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(row)), Some(Ok(col)), None) => Some((row, col)),
+        _ => None,
+    }
+}
+
+// a move typed either as "row col" or in notation such as b2
+fn read_coords(line: &str, board_size: usize) -> Result<(usize, usize), String> {
+    match parse_coords(line) {
+        Some(coords) => Ok(coords),
+        None => parse_move(line, board_size).map_err(|err| err.to_string()),
+    }
+}
+
+// prompt on stdin until a legal move is played, false if stdin runs out first
+fn human_move(game: &mut Game, lines: &mut impl Iterator<Item = io::Result<String>>) -> bool {
+    loop {
+        print!("{} to move (e.g. b2): ", game.current_piece());
+        io::stdout().flush().expect("failed to flush stdout");
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => return false,
+        };
+        match read_coords(&line, game.board().num_rows()) {
+            Ok((row, col)) => match game.make_move(row, col) {
+                Ok(()) => return true,
+                Err(err) => println!("{}", err),
+            },
+            Err(err) => println!("{}", err),
+        }
+    }
+}
+
+// the board as printed by the cli, colored when built with the color feature
+fn render(game: &Game) -> String {
+    #[cfg(feature = "color")]
+    {
+        game.to_colored_string()
+    }
+    #[cfg(not(feature = "color"))]
+    {
+        game.to_string()
+    }
+}
+
+fn announce(game: &Game) {
+    match game.winner {
+        Some(Winner::Tie) => println!("It's a tie!"),
+        Some(winner) => println!("{} wins!", winner),
+        None => println!("Game abandoned."),
+    }
+}
+
+// two players at the same terminal, reading moves from stdin until the game ends
+fn play() {
+    play_from(Game::new(), |_| {});
+}
+
+// play on from game, calling after_move with the game after every move
+fn play_from(mut game: Game, mut after_move: impl FnMut(&Game)) {
+    let mut lines = io::stdin().lock().lines();
+    println!("{}", render(&game));
+    while !game.is_finished() {
+        if !human_move(&mut game, &mut lines) {
+            break;
+        }
+        println!("{}", render(&game));
+        after_move(&game);
+    }
+    announce(&game);
+}
+
+#[cfg(feature = "serde")]
+fn save_game(game: &Game, path: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(game).map_err(|err| err.to_string())?;
+    fs::write(path, json).map_err(|err| format!("couldn't save to {}: {}", path, err))
+}
+
+// saving is attempted after every move, a failure is reported but play goes on
+#[cfg(feature = "serde")]
+fn autosave(path: &str) -> impl FnMut(&Game) + '_ {
+    move |game| {
+        if let Err(err) = save_game(game, path) {
+            eprintln!("{}", err);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn load_game(path: &str) -> Result<Game, String> {
+    let json =
+        fs::read_to_string(path).map_err(|err| format!("couldn't load {}: {}", path, err))?;
+    serde_json::from_str(&json).map_err(|err| format!("couldn't load {}: {}", path, err))
+}
+
+// a two player game that is saved to the file after every move
+#[cfg(feature = "serde")]
+fn save(args: &[String]) {
+    match args {
+        [path] => play_from(Game::new(), autosave(path)),
+        _ => usage_error("save needs a file"),
+    }
+}
+
+// resume a game written by save, continuing to save it to the same file
+#[cfg(feature = "serde")]
+fn load(args: &[String]) {
+    let path = match args {
+        [path] => path,
+        _ => usage_error("load needs a file"),
+    };
+    match load_game(path) {
+        Ok(game) => play_from(game, autosave(path)),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+// the human plays X against a computer opponent playing O
+fn vs(args: &[String]) {
+    let mut ai: Box<dyn Player> = match args {
+        [flag, level] if flag == "--difficulty" => match level.as_str() {
+            "easy" => Box::new(RandomPlayer::new(rand::random())),
+            "hard" => Box::new(MinimaxPlayer::new()),
+            _ => usage_error(&format!("unknown difficulty {:?}", level)),
+        },
+        _ => usage_error("vs needs a --difficulty"),
+    };
+
+    let mut game = Game::new();
+    let mut lines = io::stdin().lock().lines();
+    println!("{}", render(&game));
+    while !game.is_finished() {
+        match game.current_piece() {
+            Piece::X => {
+                if !human_move(&mut game, &mut lines) {
+                    break;
+                }
+            }
+            Piece::O => {
+                let (row, col) = ai.choose_move(&game);
+                println!("O plays {}", move_to_notation((row, col)));
+                game.make_move(row, col).expect("ai chose an illegal move");
+            }
+        }
+        println!("{}", render(&game));
+    }
+    announce(&game);
+}
+
+fn new_player(name: &str) -> Box<dyn Player> {
+    match name {
+        "random" => Box::new(RandomPlayer::new(rand::random())),
+        "heuristic" => Box::new(HeuristicPlayer),
+        "minimax" => Box::new(MinimaxPlayer::new()),
+        _ => unreachable!("player names are checked when parsed"),
+    }
+}
+
+// every pair of the named players plays a series with each of them taking X in turn
+fn tournament(args: &[String]) {
+    let mut players = None;
+    let mut games = 100;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage_error(&format!("{} needs a value", flag)));
+        match flag.as_str() {
+            "--players" => {
+                let names: Vec<&str> = value.split(',').collect();
+                if let Some(name) = names.iter().find(|name| !PLAYERS.contains(name)) {
+                    usage_error(&format!(
+                        "unknown player {:?}, expected one of {}",
+                        name,
+                        PLAYERS.join(", ")
+                    ));
+                }
+                players = Some(names);
+            }
+            "--games" => {
+                games = value
+                    .parse()
+                    .unwrap_or_else(|_| usage_error(&format!("invalid game count {:?}", value)));
+            }
+            _ => usage_error(&format!("unknown option {:?}", flag)),
+        }
+    }
+    let players = players.unwrap_or_else(|| usage_error("tournament needs --players"));
+
+    println!(
+        "{:<10} {:<10} {:>6} {:>6} {:>6}",
+        "X", "O", "X wins", "O wins", "ties"
+    );
+    for (i, x_name) in players.iter().enumerate() {
+        for (j, o_name) in players.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let scoreboard = play_series(
+                new_player(x_name).as_mut(),
+                new_player(o_name).as_mut(),
+                games,
+            );
+            println!(
+                "{:<10} {:<10} {:>6} {:>6} {:>6}",
+                x_name, o_name, scoreboard.x, scoreboard.o, scoreboard.ties
+            );
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("stats") => print_stats(),
+        Some("play") => play(),
+        Some("vs") => vs(&args[1..]),
+        Some("tournament") => tournament(&args[1..]),
+        #[cfg(feature = "serde")]
+        Some("save") => save(&args[1..]),
+        #[cfg(feature = "serde")]
+        Some("load") => load(&args[1..]),
+        Some(other) => usage_error(&format!("unknown command {:?}", other)),
+    }
+}
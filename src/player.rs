@@ -0,0 +1,331 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::game::{Game, Piece, Winner};
+use crate::search::{best_move, TieBreak};
+
+pub trait Player {
+    fn choose_move(&mut self, game: &Game) -> (usize, usize);
+}
+
+// play a fresh game to the end, panics if a player chooses an illegal move
+pub fn play_game(x: &mut dyn Player, o: &mut dyn Player) -> Winner {
+    let mut game = Game::new();
+    while !game.is_finished() {
+        let piece = game.current_piece();
+        let player: &mut dyn Player = match piece {
+            Piece::X => &mut *x,
+            Piece::O => &mut *o,
+        };
+        let (row, col) = player.choose_move(&game);
+        if let Err(err) = game.make_move(row, col) {
+            panic!("{:?} chose an illegal move: {}", piece, err);
+        }
+    }
+    game.winner.unwrap()
+}
+
+// tally of results over a series of games
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Scoreboard {
+    pub x: u32,
+    pub o: u32,
+    pub ties: u32,
+}
+
+impl Scoreboard {
+    pub fn record(&mut self, winner: Winner) {
+        match winner {
+            Winner::X => self.x += 1,
+            Winner::O => self.o += 1,
+            Winner::Tie => self.ties += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.x + self.o + self.ties
+    }
+}
+
+impl fmt::Display for Scoreboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // an empty scoreboard shows 0% rather than dividing by zero
+        let percent = |count: u32| 100.0 * count as f32 / self.games().max(1) as f32;
+        write!(
+            f,
+            "X: {} ({:.1}%)\nO: {} ({:.1}%)\nTies: {} ({:.1}%)",
+            self.x,
+            percent(self.x),
+            self.o,
+            percent(self.o),
+            self.ties,
+            percent(self.ties)
+        )
+    }
+}
+
+// play the same two players against each other games times
+pub fn play_series(x: &mut dyn Player, o: &mut dyn Player, games: usize) -> Scoreboard {
+    let mut scoreboard = Scoreboard::default();
+    for _ in 0..games {
+        scoreboard.record(play_game(x, o));
+    }
+    scoreboard
+}
+
+// finish game with uniformly random legal moves
+pub fn random_playout(game: &Game, rng: &mut impl Rng) -> Winner {
+    let mut game = game.clone();
+    while !game.is_finished() {
+        let (row, col) = *game.valid_moves().choose(rng).unwrap();
+        game.make_move(row, col).unwrap();
+    }
+    game.winner.unwrap()
+}
+
+// estimated probabilities of (x wins, o wins, tie) under uniform random play from
+// trials random playouts, an estimate of what GameTree computes exactly
+pub fn monte_carlo_outcomes(game: &Game, trials: usize, seed: u64) -> (f32, f32, f32) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut x_wins, mut o_wins, mut ties) = (0, 0, 0);
+    for _ in 0..trials {
+        match random_playout(game, &mut rng) {
+            Winner::X => x_wins += 1,
+            Winner::O => o_wins += 1,
+            Winner::Tie => ties += 1,
+        }
+    }
+    let n = trials as f32;
+    (x_wins as f32 / n, o_wins as f32 / n, ties as f32 / n)
+}
+
+// picks uniformly among the legal moves
+pub struct RandomPlayer {
+    rng: StdRng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn choose_move(&mut self, game: &Game) -> (usize, usize) {
+        *game
+            .valid_moves()
+            .choose(&mut self.rng)
+            .expect("no legal moves left")
+    }
+}
+
+// plays the best move found by alpha-beta search, exhaustively unless given a max depth
+pub struct MinimaxPlayer {
+    max_depth: Option<usize>,
+}
+
+impl Default for MinimaxPlayer {
+    fn default() -> Self {
+        MinimaxPlayer::new()
+    }
+}
+
+impl MinimaxPlayer {
+    pub fn new() -> Self {
+        Self { max_depth: None }
+    }
+
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+        }
+    }
+}
+
+impl Player for MinimaxPlayer {
+    fn choose_move(&mut self, game: &Game) -> (usize, usize) {
+        best_move(game, self.max_depth, TieBreak::First).expect("no legal moves left")
+    }
+}
+
+// a beatable opponent: searches depth plies ahead, but with probability randomness plays
+// a uniformly random legal move instead
+pub struct LeveledPlayer {
+    depth: usize,
+    randomness: f32,
+    rng: StdRng,
+}
+
+impl LeveledPlayer {
+    pub fn new(depth: usize, randomness: f32, seed: u64) -> Self {
+        Self {
+            depth,
+            randomness,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for LeveledPlayer {
+    fn choose_move(&mut self, game: &Game) -> (usize, usize) {
+        if self.rng.gen::<f32>() < self.randomness {
+            return *game
+                .valid_moves()
+                .choose(&mut self.rng)
+                .expect("no legal moves left");
+        }
+        best_move(game, Some(self.depth), TieBreak::First).expect("no legal moves left")
+    }
+}
+
+// plays by fixed rules rather than search: win if possible, else block the opponent, else
+// take the center, then a corner, then any other cell
+pub struct HeuristicPlayer;
+
+impl Player for HeuristicPlayer {
+    fn choose_move(&mut self, game: &Game) -> (usize, usize) {
+        let piece = game.current_piece();
+        if let Some(mv) = game.winning_move(piece).or(game.blocking_move(piece)) {
+            return mv;
+        }
+        let moves = game.valid_moves();
+        let last_row = game.board().num_rows() - 1;
+        let last_col = game.board().num_columns() - 1;
+        let center = (last_row / 2, last_col / 2);
+        let corners = [(0, 0), (0, last_col), (last_row, 0), (last_row, last_col)];
+        std::iter::once(center)
+            .chain(corners)
+            .find(|mv| moves.contains(mv))
+            .or(moves.first().copied())
+            .expect("no legal moves left")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // always takes the first legal move in row-major order
+    struct FirstMovePlayer;
+
+    impl Player for FirstMovePlayer {
+        fn choose_move(&mut self, game: &Game) -> (usize, usize) {
+            game.valid_moves()[0]
+        }
+    }
+
+    #[test]
+    fn play_game_finishes_with_a_winner() {
+        // x fills (0, 0), (0, 2) and (1, 1) and then wins at (2, 0)
+        assert_eq!(
+            play_game(&mut FirstMovePlayer, &mut FirstMovePlayer),
+            Winner::X
+        );
+        // random play finishes every game and reaches every result
+        let mut winners = std::collections::HashSet::new();
+        for seed in 0..100 {
+            winners.insert(play_game(
+                &mut RandomPlayer::new(seed),
+                &mut RandomPlayer::new(seed + 100),
+            ));
+        }
+        assert_eq!(winners.len(), 3);
+    }
+
+    #[test]
+    fn minimax_never_loses_to_random() {
+        for seed in 0..100 {
+            let winner = play_game(&mut MinimaxPlayer::new(), &mut RandomPlayer::new(seed));
+            assert_ne!(winner, Winner::O);
+            let winner = play_game(&mut RandomPlayer::new(seed), &mut MinimaxPlayer::new());
+            assert_ne!(winner, Winner::X);
+        }
+        assert_eq!(
+            play_game(&mut MinimaxPlayer::new(), &mut MinimaxPlayer::new()),
+            Winner::Tie
+        );
+    }
+
+    #[test]
+    fn monte_carlo_estimate_is_close_to_the_exact_probabilities() {
+        let (x_wins, o_wins, ties) = monte_carlo_outcomes(&Game::new(), 20_000, 7);
+        let tree = crate::game_tree::GameTree::from_cached(Game::new());
+        assert!((x_wins - tree.x_wins()).abs() < 0.02);
+        assert!((o_wins - tree.o_wins()).abs() < 0.02);
+        assert!((ties - tree.ties()).abs() < 0.02);
+        // the same seed gives the same estimate
+        assert_eq!(
+            monte_carlo_outcomes(&Game::new(), 100, 3),
+            monte_carlo_outcomes(&Game::new(), 100, 3)
+        );
+    }
+
+    #[test]
+    fn more_randomness_wins_less_against_random_play() {
+        let wins: Vec<usize> = [0.0, 0.25, 0.5, 0.75, 1.0]
+            .into_iter()
+            .map(|randomness| {
+                (0..200)
+                    .filter(|&seed| {
+                        let mut x = LeveledPlayer::new(9, randomness, seed);
+                        play_game(&mut x, &mut RandomPlayer::new(seed + 1000)) == Winner::X
+                    })
+                    .count()
+            })
+            .collect();
+        // allow a little noise between neighboring levels, but not across the whole sweep
+        assert!(
+            wins.windows(2).all(|pair| pair[1] <= pair[0] + 10),
+            "{:?}",
+            wins
+        );
+        assert!(wins[0] > wins[4] + 40, "{:?}", wins);
+    }
+
+    #[test]
+    fn scoreboard_counts_and_percentages() {
+        let mut scoreboard = Scoreboard::default();
+        for winner in [Winner::X, Winner::O, Winner::X, Winner::Tie, Winner::X] {
+            scoreboard.record(winner);
+        }
+        assert_eq!(
+            scoreboard,
+            Scoreboard {
+                x: 3,
+                o: 1,
+                ties: 1
+            }
+        );
+        assert_eq!(scoreboard.games(), 5);
+        assert_eq!(
+            scoreboard.to_string(),
+            "X: 3 (60.0%)\nO: 1 (20.0%)\nTies: 1 (20.0%)"
+        );
+        assert_eq!(
+            Scoreboard::default().to_string(),
+            "X: 0 (0.0%)\nO: 0 (0.0%)\nTies: 0 (0.0%)"
+        );
+        let scoreboard = play_series(&mut MinimaxPlayer::new(), &mut MinimaxPlayer::new(), 3);
+        assert_eq!(
+            scoreboard,
+            Scoreboard {
+                x: 0,
+                o: 0,
+                ties: 3
+            }
+        );
+    }
+
+    #[test]
+    fn heuristic_player_never_loses_to_random_when_moving_first() {
+        // moving second it can still lose, none of its rules look ahead to a fork
+        for seed in 0..200 {
+            let winner = play_game(&mut HeuristicPlayer, &mut RandomPlayer::new(seed));
+            assert_ne!(winner, Winner::O, "seed {}", seed);
+        }
+    }
+}
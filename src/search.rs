@@ -0,0 +1,310 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::cmp::Reverse;
+
+use crate::bitboard::{BitBoard, MOVE_ORDER};
+use crate::game::{lines_in, next_games, outcome_for, Game, LineStatus, Outcome, Piece, Winner};
+
+// the moves a search tries from a position, in the order it tries them
+pub type MoveGen = fn(&Game) -> Vec<(usize, usize)>;
+
+// how best_move picks among moves with the same alphabeta score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TieBreak {
+    // the first in row-major order
+    #[default]
+    First,
+    // the one that wins in the fewest plies, or loses in the most
+    FastestWin,
+    // uniformly at random, repeatable for a given seed
+    Random(u64),
+    // the one leaving the player the most cells that would complete a line
+    MostThreats,
+}
+
+// how a move compares with the best one available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveQuality {
+    // as good as any other move
+    Best,
+    // reaches the best result, but ends the game later than it could when winning or
+    // sooner than it has to when losing
+    Good,
+    // turns a win into a draw
+    Mistake,
+    // turns a win or draw into a loss
+    Blunder,
+}
+
+// +1 if piece has won, -1 if it has lost, 0 for a tie or an unfinished game
+fn winner_score(winner: Option<Winner>, piece: Piece) -> i32 {
+    match winner.map(|w| outcome_for(w, piece)) {
+        Some(Outcome::Win) => 1,
+        Some(Outcome::Loss) => -1,
+        Some(Outcome::Draw) | None => 0,
+    }
+}
+
+// +1 if the player to move has won, -1 if they have lost, 0 for a tie
+fn terminal_score(game: &Game) -> i32 {
+    winner_score(game.winner, game.current_piece())
+}
+
+// the lines of the game's board that don't hold both pieces, so that a piece on them
+// still helps one side complete a line
+fn live_lines(game: &Game) -> Vec<Vec<(usize, usize)>> {
+    let board = game.board();
+    lines_in(board.num_rows(), board.num_columns(), game.win_length())
+        .into_iter()
+        .zip(game.line_status())
+        .filter(|(_, status)| *status != LineStatus::Dead)
+        .map(|(line, _)| line)
+        .collect()
+}
+
+// the legal moves with those on the most live lines first, on an empty 3x3 board the
+// center, then the corners, then the edges, so alpha-beta finds strong moves early
+pub fn ordered_moves(game: &Game) -> Vec<(usize, usize)> {
+    let live = live_lines(game);
+    let mut moves = game.valid_moves();
+    moves.sort_by_key(|mv| Reverse(live.iter().filter(|line| line.contains(mv)).count()));
+    moves
+}
+
+// like terminal_score, but a win or loss is worth more the fewer moves it took
+fn timed_terminal_score(game: &Game) -> i32 {
+    terminal_score(game) * (game.num_empty() as i32 + 1)
+}
+
+// negamax search with alpha-beta pruning, scored from the perspective of the player to move
+pub fn alphabeta(game: &Game, alpha: i32, beta: i32) -> i32 {
+    alphabeta_to_depth(game, None, alpha, beta)
+}
+
+// alphabeta that stops after max_depth plies, scoring positions left unfinished as a tie
+pub fn alphabeta_to_depth(game: &Game, max_depth: Option<usize>, alpha: i32, beta: i32) -> i32 {
+    if game.is_finished() {
+        return terminal_score(game);
+    }
+    // a plain 3x3 game is searched on a bitboard, which is much cheaper to copy and check
+    if let Some(bits) = BitBoard::from_game(game) {
+        return bitboard_alphabeta(bits, game.current_piece(), max_depth, alpha, beta);
+    }
+    Search::new(terminal_score).negamax(game, max_depth, alpha, beta)
+}
+
+// alphabeta_to_depth on a bitboard with piece to move
+fn bitboard_alphabeta(
+    bits: BitBoard,
+    piece: Piece,
+    max_depth: Option<usize>,
+    mut alpha: i32,
+    beta: i32,
+) -> i32 {
+    if let Some(winner) = bits.winner() {
+        return winner_score(Some(winner), piece);
+    }
+    if max_depth == Some(0) {
+        return 0;
+    }
+
+    let depth = max_depth.map(|d| d - 1);
+    let mut best = -1;
+    for cell in MOVE_ORDER {
+        if bits.piece_at(cell).is_some() {
+            continue;
+        }
+        let child = bits.with_piece(cell, piece);
+        let score = -bitboard_alphabeta(child, piece.other(), depth, -beta, -alpha);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// how negamax searches and what it has counted
+struct Search {
+    // scores a finished position for the player to move
+    score_leaf: fn(&Game) -> i32,
+    move_gen: MoveGen,
+    // cut off the moves that can't change the result, without it every position is visited
+    prune: bool,
+    // positions visited so far
+    nodes: usize,
+}
+
+impl Search {
+    fn new(score_leaf: fn(&Game) -> i32) -> Self {
+        Self {
+            score_leaf,
+            move_gen: ordered_moves,
+            prune: true,
+            nodes: 0,
+        }
+    }
+
+    fn negamax(&mut self, game: &Game, max_depth: Option<usize>, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if game.is_finished() {
+            return (self.score_leaf)(game);
+        }
+        if max_depth == Some(0) {
+            return 0;
+        }
+
+        let depth = max_depth.map(|d| d - 1);
+        let mut best = -i32::MAX;
+        for (row, col) in (self.move_gen)(game) {
+            let child = game.apply(row, col).unwrap();
+            let score = -self.negamax(&child, depth, -beta, -alpha);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if self.prune && alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+}
+
+// positions visited searching game to the end trying the moves from move_gen in order,
+// with alpha-beta pruning or, when prune is false, as a plain minimax that visits every
+// position, to compare what pruning and move ordering save
+pub fn nodes_visited(game: &Game, move_gen: MoveGen, prune: bool) -> usize {
+    let mut search = Search {
+        move_gen,
+        prune,
+        ..Search::new(terminal_score)
+    };
+    search.negamax(game, None, -1, 1);
+    search.nodes
+}
+
+// a move with the highest alphabeta score for the player to move, chosen among equals by
+// tie_break
+pub fn best_move(
+    game: &Game,
+    max_depth: Option<usize>,
+    tie_break: TieBreak,
+) -> Option<(usize, usize)> {
+    if game.is_finished() {
+        return None;
+    }
+
+    let depth = max_depth.map(|d| d.saturating_sub(1));
+    let mut best = Vec::new();
+    let mut best_score = i32::MIN;
+    for (mv, child) in game.valid_moves().into_iter().zip(next_games(game)) {
+        let score = -alphabeta_to_depth(&child, depth, -1, 1);
+        if score > best_score {
+            best.clear();
+            best_score = score;
+        }
+        if score == best_score {
+            best.push((mv, child));
+        }
+    }
+
+    let piece = game.current_piece();
+    let chosen = match tie_break {
+        TieBreak::First => best.first(),
+        TieBreak::FastestWin => best.iter().min_by_key(|(_, child)| {
+            Search::new(timed_terminal_score).negamax(child, depth, -i32::MAX, i32::MAX)
+        }),
+        TieBreak::Random(seed) => best.choose(&mut StdRng::seed_from_u64(seed)),
+        TieBreak::MostThreats => best
+            .iter()
+            .min_by_key(|(_, child)| Reverse(child.winning_moves(piece).len())),
+    };
+    chosen.map(|&(mv, _)| mv)
+}
+
+// compares the result of mv under perfect play with that of the best move, panics if mv
+// isn't legal
+pub fn classify_move(game: &Game, (row, col): (usize, usize)) -> MoveQuality {
+    let score =
+        |child: &Game| -Search::new(timed_terminal_score).negamax(child, None, -i32::MAX, i32::MAX);
+    let played = score(
+        &game
+            .apply(row, col)
+            .expect("classify_move needs a legal move"),
+    );
+    let best = next_games(game).iter().map(score).max().unwrap();
+    match (best.signum(), played.signum()) {
+        _ if played == best => MoveQuality::Best,
+        (b, p) if b == p => MoveQuality::Good,
+        (1, 0) => MoveQuality::Mistake,
+        _ => MoveQuality::Blunder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_play_from_the_opening_is_a_tie() {
+        assert_eq!(alphabeta(&Game::new(), -1, 1), 0);
+    }
+
+    #[test]
+    fn alphabeta_scores_for_the_player_to_move() {
+        // x to move wins at (0, 2)
+        let game = Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1)]).unwrap();
+        assert_eq!(alphabeta(&game, -1, 1), 1);
+        // o to move has to block at (2, 2), after which x forks with (1, 0)
+        let game = Game::from_moves(&[(1, 1), (0, 1), (0, 0)]).unwrap();
+        assert_eq!(alphabeta(&game, -1, 1), -1);
+    }
+
+    #[test]
+    fn fastest_win_takes_the_immediate_win() {
+        // x wins at once in (0, 2), but (0, 0) comes first and wins a move later
+        let game = Game::from_compact(".....XOOX").unwrap();
+        assert_eq!(best_move(&game, None, TieBreak::First), Some((0, 0)));
+        assert_eq!(best_move(&game, None, TieBreak::FastestWin), Some((0, 2)));
+        // every tie break still picks a winning move
+        for tie_break in [TieBreak::Random(1), TieBreak::MostThreats] {
+            let (row, col) = best_move(&game, None, tie_break).unwrap();
+            assert_eq!(alphabeta(&game.apply(row, col).unwrap(), -1, 1), -1);
+        }
+    }
+
+    #[test]
+    fn ordered_moves_put_the_center_then_corners_first() {
+        let moves = ordered_moves(&Game::new());
+        assert_eq!(moves[0], (1, 1));
+        assert_eq!(moves[1..5], [(0, 0), (0, 2), (2, 0), (2, 2)]);
+        // lines holding both pieces no longer count
+        let game = Game::from_moves(&[(0, 0), (0, 1)]).unwrap();
+        assert_eq!(ordered_moves(&game)[..2], [(1, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn classify_move_labels_moves_against_the_best() {
+        // x wins at (0, 2), only draws by blocking at (1, 2) and loses anywhere else
+        let game = Game::from_compact("XX.OO....").unwrap();
+        assert_eq!(classify_move(&game, (0, 2)), MoveQuality::Best);
+        assert_eq!(classify_move(&game, (1, 2)), MoveQuality::Mistake);
+        assert_eq!(classify_move(&game, (2, 2)), MoveQuality::Blunder);
+        // (0, 0) still wins, just a move later than (0, 2)
+        let game = Game::from_compact(".....XOOX").unwrap();
+        assert_eq!(classify_move(&game, (0, 2)), MoveQuality::Best);
+        assert_eq!(classify_move(&game, (0, 0)), MoveQuality::Good);
+    }
+
+    #[test]
+    fn pruning_visits_fewer_positions() {
+        let game = Game::new();
+        // without pruning every position of the full game tree is visited once
+        let minimax = nodes_visited(&game, Game::valid_moves, false);
+        assert_eq!(minimax, 549946);
+        let unordered = nodes_visited(&game, Game::valid_moves, true);
+        assert!(unordered < minimax);
+        assert!(nodes_visited(&game, ordered_moves, true) < unordered);
+    }
+}
@@ -0,0 +1,59 @@
+use wasm_bindgen::prelude::*;
+
+use crate::game::Game;
+
+// thin browser-facing wrapper, all of the rules stay in Game
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: Game,
+}
+
+impl Default for WasmGame {
+    fn default() -> Self {
+        WasmGame::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { game: Game::new() }
+    }
+
+    // true if the move was legal and has been played
+    pub fn make_move(&mut self, row: usize, col: usize) -> bool {
+        self.game.make_move(row, col).is_ok()
+    }
+
+    pub fn render(&self) -> String {
+        self.game.to_string()
+    }
+
+    pub fn winner(&self) -> Option<String> {
+        self.game.winner.map(|winner| winner.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_game_plays_through_the_wrapper() {
+        let mut game = WasmGame::new();
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert!(game.make_move(row, col));
+        }
+        assert!(!game.make_move(0, 0));
+        assert_eq!(game.winner(), None);
+        assert!(game.make_move(0, 2));
+        assert_eq!(game.winner(), Some(String::from("X")));
+        assert_eq!(
+            game.render(),
+            Game::from_moves(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)])
+                .unwrap()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+#[cfg(feature = "serde")]
+use tictactoe::game::Game;
+
+// run the binary with args, feeding it input on stdin until it exits
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tictactoe"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start tictactoe");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn play_a_game_to_the_end() {
+    let output = run(&["play"], "0 0\n1 0\n0 1\n0 1\n1 1\n0 2\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("tile (0,1) already occupied by X"));
+    assert!(stdout.trim_end().ends_with("X wins!"));
+
+    let output = run(&["play"], "1 1\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.trim_end().ends_with("Game abandoned."));
+}
+
+#[test]
+fn play_against_the_computer() {
+    // the human tries every cell in turn, the occupied ones are refused
+    let cells = ["a1", "b1", "c1", "a2", "b2", "c2", "a3", "b3", "c3"];
+    let input = cells.map(|cell| format!("{}\n", cell)).concat().repeat(5);
+    for level in ["easy", "hard"] {
+        let output = run(&["vs", "--difficulty", level], &input);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("O plays "));
+        assert!(!stdout.contains("Game abandoned."));
+        if level == "hard" {
+            assert!(!stdout.contains("X wins!"));
+        }
+    }
+
+    let output = run(&["vs", "--difficulty", "impossible"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown difficulty \"impossible\""));
+}
+
+#[test]
+fn usage_lists_only_available_commands() {
+    let output = run(&["nonsense"], "");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown command"));
+    assert_eq!(stderr.contains("save <file>"), cfg!(feature = "serde"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn save_and_load_a_game() {
+    let path = std::env::temp_dir().join(format!("tictactoe-cli-{}.json", std::process::id()));
+    let path_arg = path.to_str().unwrap();
+    let read =
+        || -> Game { serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap() };
+
+    let output = run(&["save", path_arg], "b2\na1\n");
+    assert!(output.status.success());
+    assert_eq!(read(), Game::from_moves(&[(1, 1), (0, 0)]).unwrap());
+
+    let output = run(&["load", path_arg], "c3\n");
+    assert!(output.status.success());
+    assert_eq!(read(), Game::from_moves(&[(1, 1), (0, 0), (2, 2)]).unwrap());
+
+    let output = run(&["load", "/nonexistent/tictactoe.json"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("couldn't load"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn tournament_plays_every_pairing() {
+    let output = run(
+        &[
+            "tournament",
+            "--players",
+            "minimax,heuristic,random",
+            "--games",
+            "4",
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    // each of the three plays the other two as both x and o
+    assert_eq!(rows.len(), 6);
+    for row in &rows {
+        let counts: u32 = row[2..].iter().map(|n| n.parse::<u32>().unwrap()).sum();
+        assert_eq!(counts, 4);
+    }
+    let minimax_vs_heuristic = rows.iter().find(|row| row[..2] == ["minimax", "heuristic"]);
+    assert_eq!(minimax_vs_heuristic.unwrap()[3], "0");
+
+    let output = run(&["tournament", "--players", "minimax,perfect"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown player \"perfect\""));
+}